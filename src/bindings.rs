@@ -0,0 +1,676 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_egui::{EguiPreUpdateSet, input::EguiWantsInput};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub struct BindingsPlugin;
+
+impl Plugin for BindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Bindings::load(BINDINGS_FILE))
+            .init_resource::<Remapping>()
+            // before anything else reacts to the key pressed
+            .add_systems(
+                PreUpdate,
+                capture_binding.after(EguiPreUpdateSet::ProcessInput),
+            );
+    }
+}
+
+/// File the key bindings are kept in.
+const BINDINGS_FILE: &str = "assets/bindings.ron";
+
+/// Comment at the top of the bindings file.
+const BINDINGS_HEADER: &str = "\
+// Keys and mouse buttons bound to each action, read at startup.
+//
+// Each action takes a list of bindings, either Key(..) with a
+// Bevy KeyCode, or Mouse(..) with Left, Right or Middle.
+// Actions left out keep their default bindings, e.g.
+//
+//     {
+//         PlayPause: [Key(KeyK)],
+//         Bookmark1: [Key(Numpad1), Key(Digit1)],
+//     }
+";
+
+///
+/// Something the user can do with a key or mouse button.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    FlyForward,
+    FlyBack,
+    FlyLeft,
+    FlyRight,
+    FlyUp,
+    FlyDown,
+    FlyFaster,
+    GrabCursor,
+    ToggleCursorGrab,
+    ToggleMoveMode,
+    Orbit,
+    Pan,
+    ToggleTurntable,
+    ToggleFraming,
+    SaveBookmark,
+    Bookmark1,
+    Bookmark2,
+    Bookmark3,
+    Bookmark4,
+    Bookmark5,
+    Bookmark6,
+    Bookmark7,
+    Bookmark8,
+    Bookmark9,
+    PlayPause,
+    Regrow,
+    Export,
+    ToggleStats,
+    SeeThroughGround,
+    PruneTool,
+    InspectTool,
+    WindStronger,
+    WindWeaker,
+    WindGustier,
+    WindSteadier,
+}
+
+///
+/// Actions listed together in the help.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Group {
+    FreeFly,
+    Camera,
+    Bookmarks,
+    Simulation,
+    Tools,
+    Wind,
+}
+
+impl Group {
+    pub const ALL: [Group; 6] = [
+        Group::Camera,
+        Group::Bookmarks,
+        Group::FreeFly,
+        Group::Simulation,
+        Group::Tools,
+        Group::Wind,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Group::FreeFly => "Freecam Controls",
+            Group::Camera => "Camera Controls",
+            Group::Bookmarks => "Bookmarks",
+            Group::Simulation => "Simulation",
+            Group::Tools => "Tools",
+            Group::Wind => "Wind",
+        }
+    }
+
+    ///
+    /// Controls of the group that can't be remapped.
+    ///
+    fn fixed(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Group::FreeFly => &[
+                ("Mouse", "Move camera orientation"),
+                ("Scroll", "Adjust movement speed"),
+            ],
+            Group::Camera => &[("Scroll", "Zoom")],
+            Group::Bookmarks | Group::Simulation | Group::Tools | Group::Wind => &[],
+        }
+    }
+}
+
+impl Action {
+    pub const ALL: [Action; 35] = [
+        Action::FlyForward,
+        Action::FlyBack,
+        Action::FlyLeft,
+        Action::FlyRight,
+        Action::FlyUp,
+        Action::FlyDown,
+        Action::FlyFaster,
+        Action::GrabCursor,
+        Action::ToggleCursorGrab,
+        Action::ToggleMoveMode,
+        Action::Orbit,
+        Action::Pan,
+        Action::ToggleTurntable,
+        Action::ToggleFraming,
+        Action::SaveBookmark,
+        Action::Bookmark1,
+        Action::Bookmark2,
+        Action::Bookmark3,
+        Action::Bookmark4,
+        Action::Bookmark5,
+        Action::Bookmark6,
+        Action::Bookmark7,
+        Action::Bookmark8,
+        Action::Bookmark9,
+        Action::PlayPause,
+        Action::Regrow,
+        Action::Export,
+        Action::ToggleStats,
+        Action::SeeThroughGround,
+        Action::PruneTool,
+        Action::InspectTool,
+        Action::WindStronger,
+        Action::WindWeaker,
+        Action::WindGustier,
+        Action::WindSteadier,
+    ];
+
+    /// Bookmark slots 1 to 9.
+    pub const BOOKMARKS: [Action; 9] = [
+        Action::Bookmark1,
+        Action::Bookmark2,
+        Action::Bookmark3,
+        Action::Bookmark4,
+        Action::Bookmark5,
+        Action::Bookmark6,
+        Action::Bookmark7,
+        Action::Bookmark8,
+        Action::Bookmark9,
+    ];
+
+    ///
+    /// Action of bookmark slot 'slot', if it has one.
+    ///
+    pub fn bookmark(slot: u8) -> Option<Action> {
+        let index = usize::from(slot).checked_sub(1)?;
+        Action::BOOKMARKS.get(index).copied()
+    }
+
+    pub fn group(&self) -> Group {
+        match self {
+            Action::FlyForward
+            | Action::FlyBack
+            | Action::FlyLeft
+            | Action::FlyRight
+            | Action::FlyUp
+            | Action::FlyDown
+            | Action::FlyFaster
+            | Action::GrabCursor
+            | Action::ToggleCursorGrab => Group::FreeFly,
+            Action::ToggleMoveMode
+            | Action::Orbit
+            | Action::Pan
+            | Action::ToggleTurntable
+            | Action::ToggleFraming => Group::Camera,
+            Action::SaveBookmark
+            | Action::Bookmark1
+            | Action::Bookmark2
+            | Action::Bookmark3
+            | Action::Bookmark4
+            | Action::Bookmark5
+            | Action::Bookmark6
+            | Action::Bookmark7
+            | Action::Bookmark8
+            | Action::Bookmark9 => Group::Bookmarks,
+            Action::PlayPause
+            | Action::Regrow
+            | Action::Export
+            | Action::ToggleStats
+            | Action::SeeThroughGround => Group::Simulation,
+            Action::PruneTool | Action::InspectTool => Group::Tools,
+            Action::WindStronger
+            | Action::WindWeaker
+            | Action::WindGustier
+            | Action::WindSteadier => Group::Wind,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::FlyForward => "Fly forward",
+            Action::FlyBack => "Fly backwards",
+            Action::FlyLeft => "Fly sideways left",
+            Action::FlyRight => "Fly sideways right",
+            Action::FlyUp => "Fly up",
+            Action::FlyDown => "Fly down",
+            Action::FlyFaster => "Fly faster while held",
+            Action::GrabCursor => "Hold to grab cursor",
+            Action::ToggleCursorGrab => "Toggle cursor grab",
+            Action::ToggleMoveMode => "Switch between orbit and free-fly",
+            Action::Orbit => "Drag to orbit",
+            Action::Pan => "Drag to pan",
+            Action::ToggleTurntable => "Toggle turntable",
+            Action::ToggleFraming => "Toggle framing the tree",
+            Action::SaveBookmark => "Hold with a bookmark's key to save the view",
+            Action::Bookmark1 => "Bookmark 1",
+            Action::Bookmark2 => "Bookmark 2",
+            Action::Bookmark3 => "Bookmark 3",
+            Action::Bookmark4 => "Bookmark 4",
+            Action::Bookmark5 => "Bookmark 5",
+            Action::Bookmark6 => "Bookmark 6",
+            Action::Bookmark7 => "Bookmark 7",
+            Action::Bookmark8 => "Bookmark 8",
+            Action::Bookmark9 => "Bookmark 9",
+            Action::PlayPause => "Play or pause",
+            Action::Regrow => "Regrow the trees",
+            Action::Export => "Export the trees",
+            Action::ToggleStats => "Show or hide the stats",
+            Action::SeeThroughGround => "See through the ground",
+            Action::PruneTool => "Prune tool",
+            Action::InspectTool => "Inspect tool",
+            Action::WindStronger => "Stronger wind",
+            Action::WindWeaker => "Weaker wind",
+            Action::WindGustier => "Gustier wind",
+            Action::WindSteadier => "Steadier wind",
+        }
+    }
+
+    fn default_bindings(&self) -> Vec<Binding> {
+        use Binding::{Key, Mouse};
+
+        match self {
+            Action::FlyForward => vec![Key(KeyCode::KeyW)],
+            Action::FlyBack => vec![Key(KeyCode::KeyS)],
+            Action::FlyLeft => vec![Key(KeyCode::KeyA)],
+            Action::FlyRight => vec![Key(KeyCode::KeyD)],
+            Action::FlyUp => vec![Key(KeyCode::KeyE)],
+            Action::FlyDown => vec![Key(KeyCode::KeyQ)],
+            Action::FlyFaster => vec![Key(KeyCode::ShiftLeft)],
+            Action::GrabCursor => vec![Mouse(MouseButton::Left)],
+            Action::ToggleCursorGrab => vec![Key(KeyCode::KeyM)],
+            Action::ToggleMoveMode => vec![Key(KeyCode::Escape)],
+            Action::Orbit => vec![Mouse(MouseButton::Left)],
+            Action::Pan => vec![Mouse(MouseButton::Right)],
+            Action::ToggleTurntable => vec![Key(KeyCode::KeyT)],
+            Action::ToggleFraming => vec![Key(KeyCode::KeyF)],
+            Action::SaveBookmark => vec![Key(KeyCode::ControlLeft), Key(KeyCode::ControlRight)],
+            Action::Bookmark1 => vec![Key(KeyCode::Digit1)],
+            Action::Bookmark2 => vec![Key(KeyCode::Digit2)],
+            Action::Bookmark3 => vec![Key(KeyCode::Digit3)],
+            Action::Bookmark4 => vec![Key(KeyCode::Digit4)],
+            Action::Bookmark5 => vec![Key(KeyCode::Digit5)],
+            Action::Bookmark6 => vec![Key(KeyCode::Digit6)],
+            Action::Bookmark7 => vec![Key(KeyCode::Digit7)],
+            Action::Bookmark8 => vec![Key(KeyCode::Digit8)],
+            Action::Bookmark9 => vec![Key(KeyCode::Digit9)],
+            Action::PlayPause => vec![Key(KeyCode::Space)],
+            Action::Regrow => vec![Key(KeyCode::F5)],
+            Action::Export => vec![Key(KeyCode::F9)],
+            Action::ToggleStats => vec![Key(KeyCode::F3)],
+            Action::SeeThroughGround => vec![Key(KeyCode::KeyG)],
+            Action::PruneTool => vec![Key(KeyCode::KeyP)],
+            Action::InspectTool => vec![Key(KeyCode::KeyI)],
+            Action::WindStronger => vec![Key(KeyCode::Equal)],
+            Action::WindWeaker => vec![Key(KeyCode::Minus)],
+            Action::WindGustier => vec![Key(KeyCode::BracketRight)],
+            Action::WindSteadier => vec![Key(KeyCode::BracketLeft)],
+        }
+    }
+}
+
+///
+/// Key or mouse button an action is bound to.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                let name = name
+                    .strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name);
+                write!(f, "{name}")
+            }
+            Binding::Mouse(button) => write!(f, "Mouse {button:?}"),
+        }
+    }
+}
+
+///
+/// Central map from each action to the keys
+/// and mouse buttons it is bound to.
+///
+/// Actions missing from the bindings file keep their default bindings.
+///
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bindings(BTreeMap<Action, Vec<Binding>>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings(
+            Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_bindings()))
+                .collect(),
+        )
+    }
+}
+
+impl Bindings {
+    fn load(path: &str) -> Self {
+        let Ok(bindings) = std::fs::read_to_string(path) else {
+            return Bindings::default();
+        };
+
+        Bindings::parse(&bindings).unwrap_or_else(|error| {
+            warn!("invalid bindings file '{path}': {error}");
+            Bindings::default()
+        })
+    }
+
+    ///
+    /// Bindings read from 'bindings', on top of the defaults.
+    ///
+    fn parse(bindings: &str) -> Result<Self, ron::de::SpannedError> {
+        let Bindings(overrides) = ron::from_str(bindings)?;
+
+        let mut bindings = Bindings::default();
+        bindings.0.extend(overrides);
+        Ok(bindings)
+    }
+
+    ///
+    /// The bindings file's contents, keeping only
+    /// the actions bound differently from the defaults.
+    ///
+    fn to_file(&self) -> Result<String, ron::Error> {
+        let overrides: BTreeMap<&Action, &Vec<Binding>> = self
+            .0
+            .iter()
+            .filter(|(action, bindings)| **bindings != action.default_bindings())
+            .collect();
+
+        let overrides = ron::ser::to_string_pretty(&overrides, default())?;
+        Ok(format!("{BINDINGS_HEADER}{overrides}\n"))
+    }
+
+    ///
+    /// Write the bindings to the bindings file.
+    ///
+    pub fn save(&self) {
+        let bindings = match self.to_file() {
+            Ok(bindings) => bindings,
+            Err(error) => {
+                warn!("failed to serialize bindings: {error}");
+                return;
+            }
+        };
+
+        match std::fs::write(BINDINGS_FILE, bindings) {
+            Ok(()) => info!("saved bindings to '{BINDINGS_FILE}'"),
+            Err(error) => warn!("failed to save bindings to '{BINDINGS_FILE}': {error}"),
+        }
+    }
+
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    ///
+    /// Bind 'action' to 'binding' alone, taking it from other
+    /// actions of the same group, which are returned.
+    ///
+    pub fn set(&mut self, action: Action, binding: Binding) -> Vec<Action> {
+        let mut taken_from = vec![];
+
+        for (other, bindings) in self.0.iter_mut() {
+            if *other != action && other.group() == action.group() && bindings.contains(&binding) {
+                bindings.retain(|other_binding| *other_binding != binding);
+                taken_from.push(*other);
+            }
+        }
+
+        self.0.insert(action, vec![binding]);
+        taken_from
+    }
+
+    ///
+    /// Keys and mouse buttons 'action' is bound to, e.g. "ControlLeft / ControlRight".
+    ///
+    pub fn describe(&self, action: Action) -> String {
+        let names: Vec<String> = self.get(action).iter().map(Binding::to_string).collect();
+        if names.is_empty() {
+            "unbound".into()
+        } else {
+            names.join(" / ")
+        }
+    }
+
+    ///
+    /// Help text for the controls of 'group'.
+    ///
+    pub fn help(&self, group: Group) -> Help<'_> {
+        Help {
+            bindings: self,
+            group,
+        }
+    }
+}
+
+///
+/// Help text, listing the controls of a group with what they're bound to.
+///
+pub struct Help<'a> {
+    bindings: &'a Bindings,
+    group: Group,
+}
+
+impl fmt::Display for Help<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\n{}:", self.group.name())?;
+
+        for (control, description) in self.group.fixed() {
+            write!(f, "\n    {control}\t- {description}")?;
+        }
+
+        for action in Action::ALL
+            .iter()
+            .filter(|action| action.group() == self.group)
+        {
+            write!(
+                f,
+                "\n    {}\t- {}",
+                self.bindings.describe(*action),
+                action.description()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// Keyboard and mouse input, read through the bindings.
+///
+/// Keys typed into the editor's text fields don't trigger any actions.
+///
+#[derive(SystemParam)]
+pub struct Controls<'w> {
+    bindings: Res<'w, Bindings>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    egui: Res<'w, EguiWantsInput>,
+}
+
+impl Controls<'_> {
+    fn any(&self, action: Action, test: impl Fn(&Binding) -> bool) -> bool {
+        let typing = self.typing();
+
+        self.bindings
+            .get(action)
+            .iter()
+            .filter(|binding| !(typing && matches!(binding, Binding::Key(_))))
+            .any(test)
+    }
+
+    ///
+    /// Whether the keyboard is in use by the editor, e.g. typing a bookmark's name.
+    ///
+    pub fn typing(&self) -> bool {
+        self.egui.wants_any_keyboard_input()
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.any(action, |binding| match *binding {
+            Binding::Key(key) => self.keys.pressed(key),
+            Binding::Mouse(button) => self.mouse.pressed(button),
+        })
+    }
+
+    ///
+    /// Whether any of the actions of 'group' is pressed.
+    ///
+    pub fn group_pressed(&self, group: Group) -> bool {
+        Action::ALL
+            .into_iter()
+            .filter(|action| action.group() == group)
+            .any(|action| self.pressed(action))
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.any(action, |binding| match *binding {
+            Binding::Key(key) => self.keys.just_pressed(key),
+            Binding::Mouse(button) => self.mouse.just_pressed(button),
+        })
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.any(action, |binding| match *binding {
+            Binding::Key(key) => self.keys.just_released(key),
+            Binding::Mouse(button) => self.mouse.just_released(button),
+        })
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+}
+
+///
+/// Action waiting for a key or mouse button to be bound to, if any.
+///
+#[derive(Resource, Default)]
+pub struct Remapping(pub Option<Action>);
+
+///
+/// Bind the action being remapped to the next key or mouse button
+/// pressed, hiding the press from the rest of the app.
+///
+/// Escape cancels the remapping, keeping the current bindings.
+///
+pub fn capture_binding(
+    mut remapping: ResMut<Remapping>,
+    mut bindings: ResMut<Bindings>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut mouse: ResMut<ButtonInput<MouseButton>>,
+) {
+    let Some(action) = remapping.0 else {
+        return;
+    };
+
+    let key = keys.get_just_pressed().next().copied();
+    let button = mouse.get_just_pressed().next().copied();
+
+    let binding = match (key, button) {
+        (Some(KeyCode::Escape), _) => {
+            keys.clear_just_pressed(KeyCode::Escape);
+            info!("kept the bindings of {action:?}");
+            remapping.0 = None;
+            return;
+        }
+        (Some(key), _) => {
+            keys.clear_just_pressed(key);
+            Binding::Key(key)
+        }
+        (None, Some(button)) => {
+            mouse.clear_just_pressed(button);
+            Binding::Mouse(button)
+        }
+        (None, None) => return,
+    };
+
+    info!("bound {action:?} to {binding}");
+    for other in bindings.set(action, binding) {
+        warn!("unbound {binding} from {other:?}, now bound to {action:?}");
+    }
+    remapping.0 = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_file_parses() {
+        let bindings = Bindings::parse(include_str!("../assets/bindings.ron")).unwrap();
+
+        for action in Action::ALL {
+            assert!(!bindings.get(action).is_empty(), "{action:?} is unbound");
+        }
+    }
+
+    #[test]
+    fn saving_keeps_only_changed_bindings() {
+        let mut bindings = Bindings::default();
+        assert_eq!(
+            bindings.to_file().unwrap(),
+            format!("{BINDINGS_HEADER}{{}}\n")
+        );
+
+        bindings.set(Action::Bookmark1, Binding::Key(KeyCode::Numpad1));
+        let file = bindings.to_file().unwrap();
+
+        assert!(file.starts_with(BINDINGS_HEADER));
+        assert!(file.contains("Bookmark1"));
+        assert!(!file.contains("FlyForward"));
+        assert_eq!(Bindings::parse(&file).unwrap(), bindings);
+    }
+
+    #[test]
+    fn missing_actions_keep_defaults() {
+        let bindings = Bindings::parse("{ PlayPause: [Key(KeyK)] }").unwrap();
+
+        assert_eq!(
+            bindings.get(Action::PlayPause),
+            [Binding::Key(KeyCode::KeyK)]
+        );
+        assert_eq!(
+            bindings.get(Action::FlyForward),
+            [Binding::Key(KeyCode::KeyW)]
+        );
+    }
+
+    #[test]
+    fn binding_is_taken_from_the_same_group() {
+        let mut bindings = Bindings::default();
+
+        let taken_from = bindings.set(Action::ToggleFraming, Binding::Key(KeyCode::KeyT));
+        assert_eq!(taken_from, [Action::ToggleTurntable]);
+        assert!(bindings.get(Action::ToggleTurntable).is_empty());
+
+        // grabbing the cursor is in another group
+        let taken_from = bindings.set(Action::Pan, Binding::Mouse(MouseButton::Left));
+        assert_eq!(taken_from, [Action::Orbit]);
+        assert_eq!(
+            bindings.get(Action::GrabCursor),
+            [Binding::Mouse(MouseButton::Left)]
+        );
+    }
+
+    #[test]
+    fn help_lists_bindings() {
+        let help = Bindings::default().help(Group::FreeFly).to_string();
+
+        assert!(help.contains("W\t- Fly forward"));
+        assert!(help.contains("Mouse Left\t- Hold to grab cursor"));
+        assert!(!help.contains("Zoom"));
+    }
+}
@@ -0,0 +1,381 @@
+use bevy::{math::VectorSpace, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::f32::consts::{PI, TAU};
+
+use crate::bindings::{Action, Controls};
+use crate::camera::{CurrentView, GoToView, View};
+
+pub struct BookmarksPlugin;
+
+impl Plugin for BookmarksPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Bookmarks::load(BOOKMARKS_FILE))
+            .init_resource::<Playback>()
+            .add_systems(Update, (bookmark_keys, play_path).chain());
+    }
+}
+
+/// File the bookmarks and camera paths are kept in.
+const BOOKMARKS_FILE: &str = "assets/camera.ron";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub view: View,
+}
+
+///
+/// Moment, along a camera path, the camera passes through a bookmark.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// seconds since the start of the path
+    pub time: f32,
+    /// slot of the bookmark
+    pub bookmark: u8,
+}
+
+///
+/// Camera path, passing smoothly through the bookmarks of its keyframes.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CameraPath {
+    pub name: String,
+    /// keyframes, in chronological order
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    ///
+    /// View 'time' seconds into the path, or None if
+    /// none of its keyframes has a bookmark to go to.
+    ///
+    pub fn view_at(&self, bookmarks: &BTreeMap<u8, Bookmark>, time: f32) -> Option<View> {
+        let keyframes: Vec<(f32, View)> = self
+            .keyframes
+            .iter()
+            .filter_map(|keyframe| {
+                let bookmark = bookmarks.get(&keyframe.bookmark)?;
+                Some((keyframe.time, bookmark.view))
+            })
+            .collect();
+
+        let last = keyframes.len().checked_sub(1)?;
+        let next = keyframes
+            .iter()
+            .position(|(keyframe_time, _)| *keyframe_time > time)
+            .unwrap_or(last + 1);
+
+        if next == 0 {
+            return Some(keyframes[0].1);
+        }
+        if next > last {
+            return Some(keyframes[last].1);
+        }
+
+        //
+        // the keyframes around the current one
+        // shape the curve, through the next one
+        //
+        let current = next - 1;
+        let (start, end) = (keyframes[current].0, keyframes[next].0);
+        let views = [
+            keyframes[current.saturating_sub(1)].1,
+            keyframes[current].1,
+            keyframes[next].1,
+            keyframes[(next + 1).min(last)].1,
+        ];
+
+        Some(interpolate(views, (time - start) / (end - start)))
+    }
+}
+
+///
+/// Catmull-Rom spline through 'p1' and 'p2', at 't' from 0.0 to 1.0.
+///
+fn catmull_rom<T: VectorSpace>([p0, p1, p2, p3]: [T; 4], t: f32) -> T {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+///
+/// Turn 'angles' by whole turns, for each to be
+/// within half a turn from the one before.
+///
+fn unwrap_angles(mut angles: [f32; 4]) -> [f32; 4] {
+    for n in 1..angles.len() {
+        let turn = ((angles[n] - angles[n - 1] + PI) / TAU).floor();
+        angles[n] -= turn * TAU;
+    }
+
+    angles
+}
+
+///
+/// View between 'views[1]' and 'views[2]', at 't' from 0.0 to 1.0.
+///
+/// Orbit views are interpolated as orbits, circling around
+/// the focus, others as flights from one position to the next.
+///
+fn interpolate(views: [View; 4], t: f32) -> View {
+    let orbits = views.map(|view| match view {
+        View::Orbit {
+            focus,
+            yaw,
+            pitch,
+            distance,
+        } => Some((focus, yaw, pitch, distance)),
+        View::FreeFly { .. } => None,
+    });
+
+    if let [Some(o0), Some(o1), Some(o2), Some(o3)] = orbits {
+        let orbits = [o0, o1, o2, o3];
+        return View::Orbit {
+            focus: catmull_rom(orbits.map(|orbit| orbit.0), t),
+            yaw: catmull_rom(unwrap_angles(orbits.map(|orbit| orbit.1)), t),
+            pitch: catmull_rom(orbits.map(|orbit| orbit.2), t),
+            distance: catmull_rom(orbits.map(|orbit| orbit.3), t),
+        };
+    }
+
+    let flights = views.map(|view| match view.free_fly() {
+        View::FreeFly {
+            position,
+            yaw,
+            pitch,
+        } => (position, yaw, pitch),
+        View::Orbit { .. } => unreachable!(),
+    });
+
+    View::FreeFly {
+        position: catmull_rom(flights.map(|flight| flight.0), t),
+        yaw: catmull_rom(unwrap_angles(flights.map(|flight| flight.1)), t),
+        pitch: catmull_rom(flights.map(|flight| flight.2), t),
+    }
+}
+
+///
+/// Camera viewpoints saved in numbered slots, and
+/// camera paths passing through them.
+///
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bookmarks {
+    pub bookmarks: BTreeMap<u8, Bookmark>,
+    pub paths: Vec<CameraPath>,
+}
+
+impl Bookmarks {
+    fn load(path: &str) -> Self {
+        let Ok(bookmarks) = std::fs::read_to_string(path) else {
+            return Bookmarks::default();
+        };
+
+        ron::from_str(&bookmarks).unwrap_or_else(|error| {
+            warn!("invalid bookmarks file '{path}': {error}");
+            Bookmarks::default()
+        })
+    }
+
+    ///
+    /// Write the bookmarks and paths to the bookmarks file.
+    ///
+    pub fn save(&self) {
+        let bookmarks = match ron::ser::to_string_pretty(self, default()) {
+            Ok(bookmarks) => bookmarks,
+            Err(error) => {
+                warn!("failed to serialize bookmarks: {error}");
+                return;
+            }
+        };
+
+        match std::fs::write(BOOKMARKS_FILE, bookmarks) {
+            Ok(()) => info!("saved bookmarks to '{BOOKMARKS_FILE}'"),
+            Err(error) => warn!("failed to save bookmarks to '{BOOKMARKS_FILE}': {error}"),
+        }
+    }
+}
+
+///
+/// Camera path being played back.
+///
+#[derive(Resource, Default)]
+pub struct Playback {
+    /// index of the path, None when not playing
+    pub path: Option<usize>,
+    /// seconds since the start of the path
+    pub elapsed: f32,
+}
+
+impl Playback {
+    pub fn play(&mut self, path: usize) {
+        self.path = Some(path);
+        self.elapsed = 0.0;
+    }
+
+    pub fn stop(&mut self) {
+        self.path = None;
+    }
+}
+
+///
+/// Save the current view with the save bookmark key and
+/// a bookmark's key, go back to it with the bookmark's key alone.
+///
+fn bookmark_keys(
+    controls: Controls,
+    current: Res<CurrentView>,
+    mut bookmarks: ResMut<Bookmarks>,
+    mut playback: ResMut<Playback>,
+    mut go_to: EventWriter<GoToView>,
+) {
+    let saving = controls.pressed(Action::SaveBookmark);
+
+    for (slot, action) in (1..).zip(Action::BOOKMARKS) {
+        if !controls.just_pressed(action) {
+            continue;
+        }
+
+        if saving {
+            let name = bookmarks
+                .bookmarks
+                .get(&slot)
+                .map_or_else(|| format!("view {slot}"), |bookmark| bookmark.name.clone());
+            bookmarks.bookmarks.insert(
+                slot,
+                Bookmark {
+                    name,
+                    view: current.0,
+                },
+            );
+            bookmarks.save();
+        } else if let Some(bookmark) = bookmarks.bookmarks.get(&slot) {
+            playback.stop();
+            go_to.write(GoToView {
+                view: bookmark.view,
+                ease: true,
+            });
+        }
+    }
+}
+
+///
+/// Move the camera along the camera path being played back.
+///
+fn play_path(
+    time: Res<Time>,
+    bookmarks: Res<Bookmarks>,
+    mut playback: ResMut<Playback>,
+    mut go_to: EventWriter<GoToView>,
+) {
+    let Some(path) = playback.path.and_then(|path| bookmarks.paths.get(path)) else {
+        return;
+    };
+
+    playback.elapsed += time.delta_secs();
+
+    if let Some(view) = path.view_at(&bookmarks.bookmarks, playback.elapsed) {
+        go_to.write(GoToView { view, ease: false });
+    }
+
+    if playback.elapsed >= path.duration() {
+        playback.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orbit(yaw: f32) -> View {
+        View::Orbit {
+            focus: Vec3::new(yaw, 1.0, 0.0),
+            yaw,
+            pitch: -0.5,
+            distance: 10.0,
+        }
+    }
+
+    fn bookmarks() -> BTreeMap<u8, Bookmark> {
+        (1..=3)
+            .map(|slot| {
+                let bookmark = Bookmark {
+                    name: format!("view {slot}"),
+                    view: orbit(slot as f32),
+                };
+                (slot, bookmark)
+            })
+            .collect()
+    }
+
+    fn path(slots: &[u8]) -> CameraPath {
+        CameraPath {
+            name: "path".into(),
+            keyframes: slots
+                .iter()
+                .enumerate()
+                .map(|(n, slot)| Keyframe {
+                    time: n as f32 * 2.0,
+                    bookmark: *slot,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn bookmarks_file_parses() {
+        let bookmarks: Bookmarks = ron::from_str(include_str!("../assets/camera.ron")).unwrap();
+
+        for path in &bookmarks.paths {
+            for keyframe in &path.keyframes {
+                assert!(bookmarks.bookmarks.contains_key(&keyframe.bookmark));
+            }
+        }
+    }
+
+    #[test]
+    fn passes_through_keyframes() {
+        let path = path(&[1, 2, 3]);
+
+        for (n, time) in [0.0, 2.0, 4.0].into_iter().enumerate() {
+            let View::Orbit { yaw, .. } = path.view_at(&bookmarks(), time).unwrap() else {
+                panic!("orbit expected");
+            };
+            assert!((yaw - (n + 1) as f32).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn holds_still_outside_the_path() {
+        let path = path(&[1, 2, 3]);
+
+        assert_eq!(path.view_at(&bookmarks(), -1.0), Some(orbit(1.0)));
+        assert_eq!(path.view_at(&bookmarks(), 10.0), Some(orbit(3.0)));
+    }
+
+    #[test]
+    fn skips_missing_bookmarks() {
+        assert_eq!(path(&[7, 8]).view_at(&bookmarks(), 1.0), None);
+        assert_eq!(path(&[7, 2]).view_at(&bookmarks(), 3.0), Some(orbit(2.0)));
+    }
+
+    #[test]
+    fn turns_the_short_way_round() {
+        let angles = unwrap_angles([3.0, -3.0, 3.0, -3.0]);
+
+        for pair in angles.windows(2) {
+            assert!((pair[1] - pair[0]).abs() < PI);
+        }
+    }
+}
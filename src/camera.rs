@@ -2,19 +2,42 @@ use bevy::{
     input::mouse::{
         AccumulatedMouseMotion, AccumulatedMouseScroll, MouseMotion, MouseScrollUnit, MouseWheel,
     },
+    math::curve::{Curve, EaseFunction},
     prelude::*,
     window::CursorGrabMode,
 };
-use std::{f32::consts::*, fmt};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::*;
+
+use crate::bindings::{Action, Controls, Group};
+use crate::timeline::Timeline;
+use crate::tree::Tree;
+use crate::tree::branch::Branch;
+use crate::tree::inspector::Selected;
+use crate::tree::roots::InCrown;
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup)
-            .add_systems(FixedUpdate, toggle_move_mode)
+        app.init_resource::<Turntable>()
+            .init_resource::<CurrentView>()
+            .add_event::<GoToView>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    toggle_turntable,
+                    (track_view, toggle_move_mode, go_to_view, transition).chain(),
+                ),
+            )
             .add_systems(FixedUpdate, free_fly.run_if(is_free_fly_enabled))
-            .add_systems(FixedUpdate, (orbit, zoom).run_if(is_zoom_orbit_enabled));
+            .add_systems(
+                FixedUpdate,
+                (orbit, zoom, turntable, toggle_framing, frame_tree)
+                    .chain()
+                    .run_if(is_zoom_orbit_enabled),
+            );
     }
 }
 
@@ -25,14 +48,239 @@ const RADIANS_PER_DOT: f32 = 1.0 / 1000.0;
 /// Defines the starting zoom level.
 const ZOOM_ORBIT_Z: f32 = 11.2;
 
+/// Orbit camera's starting pitch, looking down on the tree.
+const ORBIT_PITCH: f32 = -PI / 3.0;
+
+/// Orbit camera's pitch limits, from straight above to a bit below the horizon.
+const MIN_PITCH: f32 = -FRAC_PI_2 + 0.01;
+const MAX_PITCH: f32 = 0.2;
+
+/// Orbit rotation, in radians, per dot the mouse is dragged.
+const ORBIT_RADIANS_PER_DOT: f32 = 0.01;
+
+/// Focus point movement, relative to the camera's distance, per dot the mouse is dragged.
+const PAN_PER_DOT: f32 = 0.0015;
+
+/// Orbit camera's distance limits.
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 120.0;
+
+/// Zoom change, relative to the camera's distance, per scroll step.
+const ZOOM_STEP: f32 = 0.1;
+
+/// Seconds the camera takes to ease from one pose to another.
+const TRANSITION_TIME: f32 = 0.8;
+
+/// Room left around the tree, when framing it.
+const FRAME_MARGIN: f32 = 1.15;
+
+///
+/// Automatic rotation of the orbit camera around the tree,
+/// pausing while the user moves the camera, or types.
+///
+#[derive(Resource, Debug)]
+pub struct Turntable {
+    pub enabled: bool,
+    /// rotation speed, in radians per second
+    pub speed: f32,
+    /// seconds without user input, before rotating again
+    pub idle_delay: f32,
+    /// time of the latest user input
+    last_input: f32,
+}
+
+impl Default for Turntable {
+    fn default() -> Self {
+        Turntable {
+            enabled: false,
+            speed: 0.15,
+            idle_delay: 3.0,
+            last_input: 0.0,
+        }
+    }
+}
+
+///
+/// Where the camera looks from, and how it moves on from there.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum View {
+    /// circling 'distance' away from 'focus'
+    Orbit {
+        #[serde(with = "vec3")]
+        focus: Vec3,
+        yaw: f32,
+        pitch: f32,
+        distance: f32,
+    },
+    /// flying freely, at 'position'
+    FreeFly {
+        #[serde(with = "vec3")]
+        position: Vec3,
+        yaw: f32,
+        pitch: f32,
+    },
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View::Orbit {
+            focus: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: ORBIT_PITCH,
+            distance: ZOOM_ORBIT_Z,
+        }
+    }
+}
+
+impl View {
+    ///
+    /// Camera's position and orientation, in world coordinates.
+    ///
+    pub fn pose(&self) -> Transform {
+        match *self {
+            View::Orbit {
+                focus,
+                yaw,
+                pitch,
+                distance,
+            } => {
+                let rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+                Transform::from_translation(focus + rotation * Vec3::Z * distance)
+                    .with_rotation(rotation)
+            }
+            View::FreeFly {
+                position,
+                yaw,
+                pitch,
+            } => Transform::from_translation(position).with_rotation(Quat::from_euler(
+                EulerRot::YXZ,
+                yaw,
+                pitch,
+                0.0,
+            )),
+        }
+    }
+
+    ///
+    /// The same view, flying freely.
+    ///
+    pub fn free_fly(&self) -> View {
+        match *self {
+            View::Orbit { yaw, pitch, .. } => View::FreeFly {
+                position: self.pose().translation,
+                yaw,
+                pitch,
+            },
+            free_fly => free_fly,
+        }
+    }
+
+    ///
+    /// The same view, circling around a point in front of the camera.
+    ///
+    pub fn orbit(&self) -> View {
+        match *self {
+            View::FreeFly {
+                position,
+                yaw,
+                pitch,
+            } => View::Orbit {
+                focus: position + self.pose().forward() * ZOOM_ORBIT_Z,
+                yaw,
+                pitch,
+                distance: ZOOM_ORBIT_Z,
+            },
+            orbit => orbit,
+        }
+    }
+}
+
+/// (De)serialize vectors as '(x, y, z)' tuples.
+mod vec3 {
+    use bevy::prelude::*;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(vector: &Vec3, serializer: S) -> Result<S::Ok, S::Error> {
+        vector.to_array().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec3, D::Error> {
+        <[f32; 3]>::deserialize(deserializer).map(Vec3::from_array)
+    }
+}
+
+///
+/// Move the camera to 'view', easing into it or right away.
+///
+#[derive(Event)]
+pub struct GoToView {
+    pub view: View,
+    pub ease: bool,
+}
+
+/// The view the camera currently has.
+#[derive(Resource, Default)]
+pub struct CurrentView(pub View);
+
 #[derive(PartialEq, Debug)]
 enum MoveMode {
     ZoomOrbit,
     FreeFly,
 }
 
-#[derive(Component, Default)]
-struct OrbitOrigo {}
+///
+/// Camera easing from one pose to another.
+///
+#[derive(Component)]
+struct CameraTransition {
+    from: Transform,
+    to: Transform,
+    /// seconds since the transition started
+    elapsed: f32,
+}
+
+impl CameraTransition {
+    fn new(from: Transform, to: Transform) -> Self {
+        CameraTransition {
+            from,
+            to,
+            elapsed: 0.0,
+        }
+    }
+}
+
+/// Filter for the camera, circling around the orbit origo.
+type OrbitCamera = (With<Camera3d>, Without<OrbitOrigo>);
+
+///
+/// Point the orbit camera circles around, looking at it.
+///
+#[derive(Component)]
+struct OrbitOrigo {
+    /// rotation around the vertical axis
+    yaw: f32,
+    /// angle above the horizon, negative when looking down
+    pitch: f32,
+    /// keep the tree framed as it grows
+    framing: bool,
+}
+
+impl Default for OrbitOrigo {
+    fn default() -> Self {
+        OrbitOrigo {
+            yaw: 0.0,
+            pitch: ORBIT_PITCH,
+            framing: false,
+        }
+    }
+}
+
+impl OrbitOrigo {
+    fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+}
 
 /// Camera controller [`Component`].
 #[derive(Component)]
@@ -45,25 +293,6 @@ struct CameraController {
     initialized: bool,
     /// Multiplier for pitch and yaw rotation speed.
     sensitivity: f32,
-    /// [`KeyCode`] for forward translation.
-    key_forward: KeyCode,
-    /// [`KeyCode`] for backward translation.
-    key_back: KeyCode,
-    /// [`KeyCode`] for left translation.
-    key_left: KeyCode,
-    /// [`KeyCode`] for right translation.
-    key_right: KeyCode,
-    /// [`KeyCode`] for up translation.
-    key_up: KeyCode,
-    /// [`KeyCode`] for down translation.
-    key_down: KeyCode,
-    /// [`KeyCode`] to use [`run_speed`](CameraController::run_speed) instead of
-    /// [`walk_speed`](CameraController::walk_speed) for translation.
-    key_run: KeyCode,
-    /// [`MouseButton`] for grabbing the mouse focus.
-    mouse_key_cursor_grab: MouseButton,
-    /// [`KeyCode`] for grabbing the keyboard focus.
-    keyboard_key_toggle_cursor_grab: KeyCode,
     /// Multiplier for unmodified translation speed.
     walk_speed: f32,
     /// Multiplier for running translation speed.
@@ -81,30 +310,12 @@ struct CameraController {
     velocity: Vec3,
 }
 
-impl CameraController {
-    fn toggle_move_mode(&mut self) {
-        self.move_mode = match self.move_mode {
-            MoveMode::ZoomOrbit => MoveMode::FreeFly,
-            MoveMode::FreeFly => MoveMode::ZoomOrbit,
-        };
-    }
-}
-
 impl Default for CameraController {
     fn default() -> Self {
         Self {
             move_mode: MoveMode::ZoomOrbit,
             initialized: false,
             sensitivity: 1.0,
-            key_forward: KeyCode::KeyW,
-            key_back: KeyCode::KeyS,
-            key_left: KeyCode::KeyA,
-            key_right: KeyCode::KeyD,
-            key_up: KeyCode::KeyE,
-            key_down: KeyCode::KeyQ,
-            key_run: KeyCode::ShiftLeft,
-            mouse_key_cursor_grab: MouseButton::Left,
-            keyboard_key_toggle_cursor_grab: KeyCode::KeyM,
             walk_speed: 5.0,
             run_speed: 15.0,
             scroll_factor: 0.1,
@@ -116,54 +327,7 @@ impl Default for CameraController {
     }
 }
 
-impl fmt::Display for CameraController {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "
-Freecam Controls:
-    Mouse\t- Move camera orientation
-    Scroll\t- Adjust movement speed
-    {:?}\t- Hold to grab cursor
-    {:?}\t- Toggle cursor grab
-    {:?} & {:?}\t- Fly forward & backwards
-    {:?} & {:?}\t- Fly sideways left & right
-    {:?} & {:?}\t- Fly up & down
-    {:?}\t- Fly faster while held",
-            self.mouse_key_cursor_grab,
-            self.keyboard_key_toggle_cursor_grab,
-            self.key_forward,
-            self.key_back,
-            self.key_left,
-            self.key_right,
-            self.key_up,
-            self.key_down,
-            self.key_run,
-        )
-    }
-}
-
-fn setup(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
-    // origin position marker
-    commands.spawn((
-        Mesh3d(meshes.add(Sphere::new(0.1))),
-        MeshMaterial3d(materials.add(Color::srgb_u8(124, 144, 255))),
-        Transform::from_xyz(0.0, 0.0, 0.0),
-    ));
-
-    // light
-    commands.spawn((
-        PointLight {
-            shadows_enabled: true,
-            ..default()
-        },
-        Transform::from_xyz(4.0, 12.0, 4.0),
-    ));
-
+fn setup(mut commands: Commands) {
     // camera
     let camera = commands
         .spawn((
@@ -177,7 +341,7 @@ fn setup(
     let orbit_origo = commands
         .spawn((
             OrbitOrigo::default(),
-            Transform::from_xyz(0.0, 0.0, 0.0).with_rotation(Quat::from_rotation_x(-PI / 3.0)),
+            Transform::from_rotation(OrbitOrigo::default().rotation()),
         ))
         .id();
 
@@ -198,77 +362,314 @@ fn is_zoom_orbit_enabled(query: Query<&CameraController>) -> bool {
 }
 
 fn toggle_move_mode(
-    key_input: Res<ButtonInput<KeyCode>>,
-    mut commands: Commands,
-    mut query: Query<&mut CameraController>,
-    orbit_query: Query<Entity, With<OrbitOrigo>>,
-    camera_query: Query<Entity, With<Camera3d>>,
+    controls: Controls,
+    current: Res<CurrentView>,
+    mut go_to: EventWriter<GoToView>,
 ) {
-    fn setup_zoom_orbit(commands: &mut Commands, orbit_origo: Entity, camera: Entity) {
-        commands.entity(orbit_origo).add_child(camera);
-        commands
-            .entity(camera)
-            .insert(Transform::from_xyz(0.0, 0.0, ZOOM_ORBIT_Z));
+    if !controls.just_pressed(Action::ToggleMoveMode) {
+        return;
     }
 
-    fn setup_free_fly(commands: &mut Commands, orbit_origo: Entity) {
-        commands.entity(orbit_origo).remove::<Children>();
-    }
+    //
+    // fly on from where the orbit camera is, or circle
+    // around a point in front of the free-flying camera
+    //
+    let view = match current.0 {
+        View::Orbit { .. } => current.0.free_fly(),
+        View::FreeFly { .. } => current.0.orbit(),
+    };
+
+    go_to.write(GoToView { view, ease: true });
+}
 
-    if !key_input.just_pressed(KeyCode::Escape) {
+///
+/// Keep track of the view the camera currently has.
+///
+fn track_view(
+    mut current: ResMut<CurrentView>,
+    query: Query<(&CameraController, &Transform), OrbitCamera>,
+    orbit_query: Query<(&Transform, &OrbitOrigo)>,
+) {
+    let (camera_ctrl, camera_trans) = query.single().unwrap();
+    let (orbit_trans, origo) = orbit_query.single().unwrap();
+
+    current.0 = match camera_ctrl.move_mode {
+        MoveMode::ZoomOrbit => View::Orbit {
+            focus: orbit_trans.translation,
+            yaw: origo.yaw,
+            pitch: origo.pitch,
+            distance: camera_trans.translation.z,
+        },
+        MoveMode::FreeFly => {
+            let (yaw, pitch, _) = camera_trans.rotation.to_euler(EulerRot::YXZ);
+            View::FreeFly {
+                position: camera_trans.translation,
+                yaw,
+                pitch,
+            }
+        }
+    };
+}
+
+///
+/// Move the camera to the requested view, switching
+/// between orbit and free-fly modes as needed.
+///
+/// Eased moves start from the camera's current
+/// world pose, thus the view never jumps.
+///
+fn go_to_view(
+    mut commands: Commands,
+    mut requests: EventReader<GoToView>,
+    mut query: Query<
+        (
+            Entity,
+            &mut CameraController,
+            &mut Transform,
+            &GlobalTransform,
+        ),
+        OrbitCamera,
+    >,
+    mut orbit_query: Query<(Entity, &mut Transform, &mut OrbitOrigo)>,
+) {
+    let Some(request) = requests.read().last() else {
         return;
-    }
+    };
 
-    let mut camera_ctrl = query.single_mut().unwrap();
-    camera_ctrl.toggle_move_mode();
+    let (camera, mut camera_ctrl, mut camera_trans, global_trans) = query.single_mut().unwrap();
+    let (orbit_origo, mut orbit_trans, mut origo) = orbit_query.single_mut().unwrap();
+    let world = global_trans.compute_transform();
+
+    let (from, to) = match request.view {
+        View::Orbit {
+            focus,
+            yaw,
+            pitch,
+            distance,
+        } => {
+            camera_ctrl.move_mode = MoveMode::ZoomOrbit;
+
+            origo.yaw = yaw;
+            origo.pitch = pitch.clamp(MIN_PITCH, MAX_PITCH);
+            origo.framing = false;
+            *orbit_trans = Transform::from_translation(focus).with_rotation(origo.rotation());
+
+            commands.entity(orbit_origo).add_child(camera);
+
+            // camera's current pose, relative to the orbit origo
+            let from = Transform::from_matrix(
+                orbit_trans.compute_matrix().inverse() * world.compute_matrix(),
+            );
+            let to = Transform::from_xyz(0.0, 0.0, distance.clamp(MIN_ZOOM, MAX_ZOOM));
+            (from, to)
+        }
+        View::FreeFly { yaw, pitch, .. } => {
+            camera_ctrl.move_mode = MoveMode::FreeFly;
+            camera_ctrl.yaw = yaw;
+            camera_ctrl.pitch = pitch;
 
-    let orbit_origo = orbit_query.single().unwrap();
+            commands.entity(camera).remove::<ChildOf>();
 
-    match camera_ctrl.move_mode {
-        MoveMode::ZoomOrbit => {
-            setup_zoom_orbit(&mut commands, orbit_origo, camera_query.single().unwrap())
+            (world, request.view.pose())
         }
-        MoveMode::FreeFly => {
-            setup_free_fly(&mut commands, orbit_origo);
+    };
+
+    if request.ease {
+        *camera_trans = from;
+        commands
+            .entity(camera)
+            .insert(CameraTransition::new(from, to));
+    } else {
+        *camera_trans = to;
+        commands.entity(camera).remove::<CameraTransition>();
+    }
+}
+
+///
+/// Ease the camera from one pose to another,
+/// relative to its parent, if it has one.
+///
+fn transition(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut CameraTransition)>,
+) {
+    for (entity_id, mut transform, mut transition) in query.iter_mut() {
+        transition.elapsed += time.delta_secs();
+
+        let progress = (transition.elapsed / TRANSITION_TIME).min(1.0);
+        let eased = EaseFunction::CubicInOut.sample_clamped(progress);
+
+        transform.translation = transition
+            .from
+            .translation
+            .lerp(transition.to.translation, eased);
+        transform.rotation = transition
+            .from
+            .rotation
+            .slerp(transition.to.rotation, eased);
+
+        if progress >= 1.0 {
+            commands.entity(entity_id).remove::<CameraTransition>();
         }
     }
 }
 
 fn orbit(
     mut evr_motion: EventReader<MouseMotion>,
-    buttons: Res<ButtonInput<MouseButton>>,
-    mut query: Query<&mut Transform, With<OrbitOrigo>>,
+    controls: Controls,
+    mut query: Query<(&mut Transform, &mut OrbitOrigo)>,
+    camera_query: Query<&Transform, OrbitCamera>,
 ) {
-    //
-    // orbit camera around origin, when
-    // mouse is dragged horizontally
-    //
-    if !buttons.pressed(MouseButton::Left) {
-        return;
+    let (mut trans, mut origo) = query.single_mut().unwrap();
+    let distance = camera_query.single().unwrap().translation.z;
+
+    for ev in evr_motion.read() {
+        //
+        // orbit camera around the focus point,
+        // when mouse is dragged with the orbit button
+        //
+        if controls.pressed(Action::Orbit) {
+            origo.yaw -= ev.delta.x * ORBIT_RADIANS_PER_DOT;
+            origo.pitch =
+                (origo.pitch - ev.delta.y * ORBIT_RADIANS_PER_DOT).clamp(MIN_PITCH, MAX_PITCH);
+        }
+
+        //
+        // and move the focus point along with
+        // the mouse, dragged with the pan button
+        //
+        if controls.pressed(Action::Pan) {
+            let pan =
+                (trans.right() * -ev.delta.x + trans.up() * ev.delta.y) * PAN_PER_DOT * distance;
+            trans.translation += pan;
+            origo.framing = false;
+        }
     }
 
-    let mut trans = query.single_mut().unwrap();
+    trans.rotation = origo.rotation();
+}
 
-    for ev in evr_motion.read() {
-        trans.rotate_y(ev.delta.x * -0.01);
+fn toggle_turntable(controls: Controls, mut turntable: ResMut<Turntable>) {
+    if controls.just_pressed(Action::ToggleTurntable) {
+        turntable.enabled = !turntable.enabled;
     }
 }
 
-fn zoom(mut evr_wheel: EventReader<MouseWheel>, mut query: Query<&mut Transform, With<Camera3d>>) {
+///
+/// Rotate the orbit camera, unless the camera
+/// has been moved by the user not long ago.
+///
+fn turntable(
+    time: Res<Time>,
+    controls: Controls,
+    accumulated_mouse_motion: Res<AccumulatedMouseMotion>,
+    accumulated_mouse_scroll: Res<AccumulatedMouseScroll>,
+    mut turntable: ResMut<Turntable>,
+    mut query: Query<(&mut Transform, &mut OrbitOrigo)>,
+) {
+    let now = time.elapsed_secs();
+
+    let user_input = controls.group_pressed(Group::Camera)
+        || controls.group_pressed(Group::Bookmarks)
+        || accumulated_mouse_motion.delta != Vec2::ZERO
+        || accumulated_mouse_scroll.delta != Vec2::ZERO;
+    if user_input {
+        turntable.last_input = now;
+    }
+
+    if !turntable.enabled || now - turntable.last_input < turntable.idle_delay {
+        return;
+    }
+
+    let (mut trans, mut origo) = query.single_mut().unwrap();
+    origo.yaw += turntable.speed * time.delta_secs();
+    trans.rotation = origo.rotation();
+}
+
+fn zoom(
+    mut evr_wheel: EventReader<MouseWheel>,
+    mut query: Query<&mut Transform, With<Camera3d>>,
+    mut orbit_query: Query<&mut OrbitOrigo>,
+) {
     let mut trans = query.single_mut().unwrap();
 
     for ev in evr_wheel.read() {
-        trans.translation.z -= ev.y * 0.1;
+        let distance = trans.translation.z * (1.0 - ev.y * ZOOM_STEP);
+        trans.translation.z = distance.clamp(MIN_ZOOM, MAX_ZOOM);
+        orbit_query.single_mut().unwrap().framing = false;
     }
 }
 
+fn toggle_framing(controls: Controls, mut query: Query<&mut OrbitOrigo>) {
+    if controls.just_pressed(Action::ToggleFraming) {
+        let mut origo = query.single_mut().unwrap();
+        origo.framing = !origo.framing;
+    }
+}
+
+///
+/// Center the orbit camera on the tree the selected branch
+/// grows on, or on the first tree, and move it just far enough
+/// for the whole tree to fit in view, following it as it grows.
+///
+fn frame_tree(
+    timeline: Res<Timeline>,
+    trees: Query<Entity, With<Tree>>,
+    selected: Query<&Branch, With<Selected>>,
+    branches: Query<(&Branch, &GlobalTransform), InCrown>,
+    mut query: Query<(&mut Transform, &OrbitOrigo)>,
+    mut camera_query: Query<(&mut Transform, &Projection), OrbitCamera>,
+) {
+    let (mut trans, origo) = query.single_mut().unwrap();
+    if !origo.framing {
+        return;
+    }
+
+    let tree = match selected.single() {
+        Ok(branch) => Some(branch.tree()),
+        Err(_) => trees.iter().next(),
+    };
+    let Some(tree) = tree else {
+        return;
+    };
+
+    let now = timeline.now();
+    let (min, max) = branches
+        .iter()
+        .filter(|(branch, _)| branch.tree() == tree)
+        .flat_map(|(branch, transform)| [transform.translation(), branch.tip(transform, now)])
+        .fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), point| {
+            (min.min(point), max.max(point))
+        });
+    if min.x > max.x {
+        return;
+    }
+
+    trans.translation = (min + max) / 2.0;
+
+    //
+    // fit the tree's bounding sphere in the
+    // narrower of the two fields of view
+    //
+    let (mut camera_trans, projection) = camera_query.single_mut().unwrap();
+    let Projection::Perspective(perspective) = projection else {
+        return;
+    };
+    let horizontal_fov = 2.0 * ((perspective.fov / 2.0).tan() * perspective.aspect_ratio).atan();
+    let fov = perspective.fov.min(horizontal_fov);
+
+    let radius = ((max - min).length() / 2.0).max(MIN_ZOOM);
+    let distance = radius * FRAME_MARGIN / (fov / 2.0).sin();
+    camera_trans.translation.z = distance.clamp(MIN_ZOOM, MAX_ZOOM);
+}
+
 fn free_fly(
     time: Res<Time>,
     mut windows: Query<&mut Window>,
     accumulated_mouse_motion: Res<AccumulatedMouseMotion>,
     accumulated_mouse_scroll: Res<AccumulatedMouseScroll>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
-    key_input: Res<ButtonInput<KeyCode>>,
+    controls: Controls,
     mut toggle_cursor_grab: Local<bool>,
     mut mouse_cursor_grab: Local<bool>,
     mut query: Query<(&mut Transform, &mut CameraController), With<Camera>>,
@@ -279,12 +680,10 @@ fn free_fly(
         return;
     };
 
+    // yaw and pitch are set on entering free-fly mode
     if !controller.initialized {
-        let (yaw, pitch, _roll) = transform.rotation.to_euler(EulerRot::YXZ);
-        controller.yaw = yaw;
-        controller.pitch = pitch;
         controller.initialized = true;
-        info!("{}", *controller);
+        info!("{}", controls.bindings().help(Group::FreeFly));
     }
 
     let mut scroll = 0.0;
@@ -299,35 +698,35 @@ fn free_fly(
 
     // Handle key input
     let mut axis_input = Vec3::ZERO;
-    if key_input.pressed(controller.key_forward) {
+    if controls.pressed(Action::FlyForward) {
         axis_input.z += 1.0;
     }
-    if key_input.pressed(controller.key_back) {
+    if controls.pressed(Action::FlyBack) {
         axis_input.z -= 1.0;
     }
-    if key_input.pressed(controller.key_right) {
+    if controls.pressed(Action::FlyRight) {
         axis_input.x += 1.0;
     }
-    if key_input.pressed(controller.key_left) {
+    if controls.pressed(Action::FlyLeft) {
         axis_input.x -= 1.0;
     }
-    if key_input.pressed(controller.key_up) {
+    if controls.pressed(Action::FlyUp) {
         axis_input.y += 1.0;
     }
-    if key_input.pressed(controller.key_down) {
+    if controls.pressed(Action::FlyDown) {
         axis_input.y -= 1.0;
     }
 
     let mut cursor_grab_change = false;
-    if key_input.just_pressed(controller.keyboard_key_toggle_cursor_grab) {
+    if controls.just_pressed(Action::ToggleCursorGrab) {
         *toggle_cursor_grab = !*toggle_cursor_grab;
         cursor_grab_change = true;
     }
-    if mouse_button_input.just_pressed(controller.mouse_key_cursor_grab) {
+    if controls.just_pressed(Action::GrabCursor) {
         *mouse_cursor_grab = true;
         cursor_grab_change = true;
     }
-    if mouse_button_input.just_released(controller.mouse_key_cursor_grab) {
+    if controls.just_released(Action::GrabCursor) {
         *mouse_cursor_grab = false;
         cursor_grab_change = true;
     }
@@ -335,7 +734,7 @@ fn free_fly(
 
     // Apply movement update
     if axis_input != Vec3::ZERO {
-        let max_speed = if key_input.pressed(controller.key_run) {
+        let max_speed = if controls.pressed(Action::FlyFaster) {
             controller.run_speed
         } else {
             controller.walk_speed
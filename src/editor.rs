@@ -0,0 +1,405 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiGlobalSettings, EguiPlugin, EguiPrimaryContextPass, egui};
+use std::f32::consts::PI;
+
+use crate::bindings::{Action, Bindings, Controls, Group, Remapping};
+use crate::bookmarks::{Bookmarks, Playback};
+use crate::camera::{GoToView, Turntable};
+use crate::season::YEAR_LENGTH;
+use crate::sun::Sun;
+use crate::timeline::Timeline;
+use crate::tree::Regrow;
+use crate::tree::Tree;
+use crate::tree::branch::Branch;
+use crate::tree::export::Export;
+use crate::tree::growth::GrowthCurve;
+use crate::tree::inspector::Selected;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(EguiPlugin::default())
+            // keep the camera still while dragging sliders
+            .insert_resource(EguiGlobalSettings {
+                enable_absorb_bevy_input_system: true,
+                ..default()
+            })
+            .add_event::<Regrow>()
+            .add_event::<Export>()
+            .add_systems(Update, shortcuts)
+            .add_systems(
+                EguiPrimaryContextPass,
+                (
+                    panel,
+                    timeline_panel,
+                    sun_panel,
+                    camera_panel,
+                    bindings_panel,
+                ),
+            );
+    }
+}
+
+///
+/// Regrow and export with their keys, as well as with the buttons.
+///
+fn shortcuts(controls: Controls, mut regrow: EventWriter<Regrow>, mut export: EventWriter<Export>) {
+    if controls.just_pressed(Action::Regrow) {
+        regrow.write(Regrow);
+    }
+    if controls.just_pressed(Action::Export) {
+        export.write(Export);
+    }
+}
+
+fn slider(ui: &mut egui::Ui, label: &str, value: &mut f32, range: std::ops::RangeInclusive<f32>) {
+    ui.add(egui::Slider::new(value, range).text(label));
+}
+
+///
+/// Sliders for the parameters of the species' growth curve,
+/// setting the length of its branches as they age.
+///
+fn growth_curve_sliders(ui: &mut egui::Ui, curve: &mut GrowthCurve) {
+    match curve {
+        GrowthCurve::LinearLog { scale } => {
+            ui.label("linear-log");
+            slider(ui, "scale", scale, 0.1..=5.0);
+        }
+        GrowthCurve::Logistic {
+            max_length,
+            rate,
+            midpoint,
+        } => {
+            ui.label("logistic");
+            slider(ui, "max length", max_length, 0.5..=20.0);
+            slider(ui, "rate", rate, 0.01..=0.5);
+            slider(ui, "midpoint", midpoint, 0.0..=100.0);
+        }
+        GrowthCurve::Gompertz {
+            max_length,
+            displacement,
+            rate,
+        } => {
+            ui.label("Gompertz");
+            slider(ui, "max length", max_length, 0.5..=20.0);
+            slider(ui, "displacement", displacement, 0.1..=10.0);
+            slider(ui, "rate", rate, 0.005..=0.5);
+        }
+        GrowthCurve::Richards {
+            max_length,
+            rate,
+            midpoint,
+            shape,
+        } => {
+            ui.label("Richards");
+            slider(ui, "max length", max_length, 0.5..=20.0);
+            slider(ui, "rate", rate, 0.01..=0.5);
+            slider(ui, "midpoint", midpoint, 0.0..=100.0);
+            slider(ui, "shape", shape, 0.1..=5.0);
+        }
+    }
+}
+
+///
+/// Panel with sliders for the growth parameters of the tree
+/// the selected branch grows on, or of the first tree.
+///
+fn panel(
+    mut contexts: EguiContexts,
+    mut trees: Query<&mut Tree>,
+    selected: Query<&Branch, With<Selected>>,
+    mut regrow: EventWriter<Regrow>,
+    mut export: EventWriter<Export>,
+) -> Result {
+    let tree = match selected.single() {
+        Ok(branch) => trees.get_mut(branch.tree()).ok(),
+        Err(_) => trees.iter_mut().next(),
+    };
+    let Some(mut tree) = tree else {
+        return Ok(());
+    };
+
+    egui::Window::new("Growth parameters").show(contexts.ctx_mut()?, |ui| {
+        ui.heading("Branching");
+        let parameters = tree.parameters_mut();
+        slider(
+            ui,
+            "branch spacing",
+            &mut parameters.branch_spacing,
+            0.2..=2.0,
+        );
+        slider(
+            ui,
+            "branch height",
+            &mut parameters.branch_height,
+            0.1..=1.0,
+        );
+        slider(
+            ui,
+            "growth rate",
+            &mut parameters.branch_growth_rate,
+            0.1..=1.0,
+        );
+
+        ui.heading("Branch angles");
+        slider(ui, "range width", &mut parameters.range_width, 0.0..=PI);
+        slider(ui, "bell width", &mut parameters.bell_width, 0.01..=1.0);
+        let species = tree.species_mut();
+        slider(
+            ui,
+            "base inclination",
+            &mut species.base_inclination,
+            0.0..=PI,
+        );
+        slider(
+            ui,
+            "order inclination",
+            &mut species.order_inclination,
+            0.0..=1.0,
+        );
+        slider(
+            ui,
+            "apical inclination",
+            &mut species.apical_inclination,
+            0.0..=1.5,
+        );
+        slider(
+            ui,
+            "inclination jitter",
+            &mut species.inclination_jitter,
+            0.0..=1.0,
+        );
+
+        ui.heading("Growth curve");
+        growth_curve_sliders(ui, &mut species.growth_curve);
+
+        ui.heading("Leaves");
+        slider(
+            ui,
+            "leaf inclination",
+            &mut species.leaf_inclination,
+            0.0..=PI,
+        );
+        slider(ui, "leaf jitter", &mut species.leaf_jitter, 0.0..=1.0);
+        let parameters = tree.parameters_mut();
+        slider(ui, "leaf spacing", &mut parameters.leaf_spacing, 0.05..=1.0);
+
+        ui.heading("Roots");
+        slider(
+            ui,
+            "root growth rate",
+            &mut parameters.root_growth_rate,
+            0.05..=1.0,
+        );
+        slider(ui, "root spacing", &mut parameters.root_spacing, 0.05..=1.0);
+        slider(
+            ui,
+            "root inclination",
+            &mut parameters.root_inclination,
+            0.0..=PI,
+        );
+        slider(ui, "root ratio", &mut parameters.root_ratio, 0.05..=1.0);
+
+        ui.heading("Mesh");
+        ui.add(egui::Slider::new(&mut parameters.segments, 3..=16).text("segments"));
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("seed");
+            ui.add(egui::DragValue::new(&mut parameters.seed));
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Regrow").clicked() {
+                regrow.write(Regrow);
+            }
+            if ui.button("Export").clicked() {
+                export.write(Export);
+            }
+        });
+    });
+
+    Ok(())
+}
+
+///
+/// Slider scrubbing through the life of the trees, with playback controls.
+///
+fn timeline_panel(mut contexts: EguiContexts, mut timeline: ResMut<Timeline>) -> Result {
+    egui::TopBottomPanel::bottom("timeline").show(contexts.ctx_mut()?, |ui| {
+        ui.horizontal(|ui| {
+            let label = if timeline.playing { "Pause" } else { "Play" };
+            if ui.button(label).clicked() {
+                timeline.playing = !timeline.playing;
+            }
+
+            if ui
+                .add_enabled(!timeline.is_live(), egui::Button::new("Live"))
+                .clicked()
+            {
+                timeline.go_live();
+            }
+
+            ui.add(
+                egui::Slider::new(&mut timeline.speed, 0.25..=8.0)
+                    .logarithmic(true)
+                    .text("speed"),
+            );
+
+            let mut now = timeline.now();
+            ui.spacing_mut().slider_width = ui.available_width() - 80.0;
+            let scrubbed = ui
+                .add(
+                    egui::Slider::new(&mut now, 0.0..=timeline.live()).custom_formatter(
+                        |time, _| format!("year {:.1}", time / YEAR_LENGTH as f64),
+                    ),
+                )
+                .changed();
+
+            if scrubbed {
+                timeline.scrub(now);
+            }
+        });
+    });
+
+    Ok(())
+}
+
+///
+/// Where the trees grow, and how fast the days go by.
+///
+fn sun_panel(mut contexts: EguiContexts, timeline: Res<Timeline>, mut sun: ResMut<Sun>) -> Result {
+    egui::Window::new("Sun")
+        .default_open(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("latitude");
+                ui.drag_angle(&mut sun.latitude);
+            });
+            sun.latitude = sun.latitude.clamp(-PI / 2.0, PI / 2.0);
+
+            slider(ui, "day length", &mut sun.day_length, 5.0..=240.0);
+
+            let hours = sun.time_of_day(timeline.now()) * 24.0;
+            ui.label(format!(
+                "time of day {:02}:{:02}",
+                hours as u32,
+                (hours.fract() * 60.0) as u32
+            ));
+        });
+
+    Ok(())
+}
+
+///
+/// Turntable rotation of the orbit camera, bookmarked
+/// views to go to, and camera paths to play back.
+///
+fn camera_panel(
+    mut contexts: EguiContexts,
+    bindings: Res<Bindings>,
+    mut turntable: ResMut<Turntable>,
+    mut bookmarks: ResMut<Bookmarks>,
+    mut playback: ResMut<Playback>,
+    mut go_to: EventWriter<GoToView>,
+) -> Result {
+    egui::Window::new("Camera")
+        .default_open(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            let toggle = bindings.describe(Action::ToggleTurntable);
+            ui.checkbox(&mut turntable.enabled, format!("turntable ({toggle})"));
+            slider(ui, "speed", &mut turntable.speed, -1.0..=1.0);
+            slider(ui, "idle delay", &mut turntable.idle_delay, 0.0..=30.0);
+
+            ui.heading("Bookmarks");
+            let save = bindings.describe(Action::SaveBookmark);
+            ui.label(format!(
+                "{save} + a bookmark's key to save, its key to go to"
+            ));
+            for (slot, bookmark) in bookmarks.bookmarks.iter_mut() {
+                ui.horizontal(|ui| {
+                    let key = Action::bookmark(*slot)
+                        .map_or_else(|| slot.to_string(), |action| bindings.describe(action));
+                    ui.label(key);
+                    ui.text_edit_singleline(&mut bookmark.name);
+                    if ui.button("Go").clicked() {
+                        playback.stop();
+                        go_to.write(GoToView {
+                            view: bookmark.view,
+                            ease: true,
+                        });
+                    }
+                });
+            }
+
+            ui.heading("Paths");
+            for (index, path) in bookmarks.paths.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} ({:.0} s)", path.name, path.duration()));
+                    if playback.path == Some(index) {
+                        if ui.button("Stop").clicked() {
+                            playback.stop();
+                        }
+                    } else if ui.button("Play").clicked() {
+                        playback.play(index);
+                    }
+                });
+            }
+
+            if ui.button("Save").clicked() {
+                bookmarks.save();
+            }
+        });
+
+    Ok(())
+}
+
+///
+/// Key bindings of every action, each remapped by clicking
+/// it and pressing the key or mouse button to bind it to.
+///
+fn bindings_panel(
+    mut contexts: EguiContexts,
+    mut bindings: ResMut<Bindings>,
+    mut remapping: ResMut<Remapping>,
+) -> Result {
+    egui::Window::new("Key bindings")
+        .default_open(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            for group in Group::ALL {
+                ui.heading(group.name());
+                egui::Grid::new(group.name()).show(ui, |ui| {
+                    for action in Action::ALL
+                        .into_iter()
+                        .filter(|action| action.group() == group)
+                    {
+                        ui.label(action.description());
+
+                        let waiting = remapping.0 == Some(action);
+                        let label = if waiting {
+                            "press a key, Esc to cancel...".into()
+                        } else {
+                            bindings.describe(action)
+                        };
+                        if ui.selectable_label(waiting, label).clicked() {
+                            remapping.0 = if waiting { None } else { Some(action) };
+                        }
+                        ui.end_row();
+                    }
+                });
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    bindings.save();
+                }
+                if ui.button("Defaults").clicked() {
+                    *bindings = Bindings::default();
+                    remapping.0 = None;
+                }
+            });
+        });
+
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use bevy::{
+    pbr::{Atmosphere, NotShadowCaster},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::sun::Sun;
+use crate::timeline::Timeline;
+
+pub struct EnvironmentPlugin;
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Environment::load(SCENE_FILE))
+            .add_systems(Startup, setup)
+            .add_systems(Update, (configure_camera, update));
+    }
+}
+
+/// Scene file the environment is configured in.
+const SCENE_FILE: &str = "assets/scene.ron";
+
+/// Radius of the sky dome, well within the camera's far plane.
+const SKY_RADIUS: f32 = 500.0;
+
+/// Tint of the sky dome at night.
+const NIGHT_TINT: Color = Color::srgb(0.04, 0.05, 0.12);
+
+///
+/// Surroundings of the trees, as configured in the scene file.
+///
+/// Missing fields take their default values, thus an empty
+/// 'Environment()' gives a gradient sky with a light fog.
+///
+#[derive(Resource, Deserialize, Debug)]
+#[serde(default)]
+pub struct Environment {
+    /// what the sky looks like
+    pub sky: Sky,
+    /// distance fog, if any
+    pub fog: Option<Fog>,
+    /// environment map for reflections, if any
+    pub environment_map: Option<EnvironmentMap>,
+}
+
+#[derive(Deserialize, Debug)]
+pub enum Sky {
+    /// plain color, following the time of day
+    Plain,
+    /// gradient from the horizon up to the zenith, in sRGB
+    Gradient { horizon: [f32; 3], zenith: [f32; 3] },
+    /// Bevy's physically based atmosphere, lit by the sun
+    Atmosphere,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Fog {
+    /// color of the fog in daylight, in sRGB
+    pub color: [f32; 3],
+    /// distance at which things fade into the fog
+    pub visibility: f32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EnvironmentMap {
+    /// diffuse cubemap, relative to the assets folder
+    pub diffuse: String,
+    /// specular cubemap, relative to the assets folder
+    pub specular: String,
+    /// brightness of the map in daylight
+    pub intensity: f32,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Environment {
+            sky: Sky::Gradient {
+                horizon: [0.78, 0.85, 0.92],
+                zenith: [0.30, 0.50, 0.85],
+            },
+            fog: Some(Fog {
+                color: [0.70, 0.78, 0.85],
+                visibility: 90.0,
+            }),
+            environment_map: None,
+        }
+    }
+}
+
+impl Environment {
+    ///
+    /// Load the environment from scene file at 'path',
+    /// falling back to the defaults when it can't be read.
+    ///
+    fn load(path: &str) -> Self {
+        let scene = match std::fs::read_to_string(path) {
+            Ok(scene) => scene,
+            Err(error) => {
+                info!("no scene file '{path}' ({error}), using the default environment");
+                return Environment::default();
+            }
+        };
+
+        ron::from_str(&scene).unwrap_or_else(|error| {
+            warn!("invalid scene file '{path}': {error}, using the default environment");
+            Environment::default()
+        })
+    }
+}
+
+fn srgb([red, green, blue]: [f32; 3]) -> Color {
+    Color::srgb(red, green, blue)
+}
+
+/// Sphere around the camera, painted with the sky's gradient.
+#[derive(Component)]
+struct SkyDome;
+
+///
+/// Sky dome mesh, its vertices colored from 'horizon', at
+/// and below the horizon, up to 'zenith' straight above.
+///
+fn sky_dome_mesh(horizon: Color, zenith: Color) -> Mesh {
+    let mut mesh = Sphere::new(SKY_RADIUS).mesh().uv(48, 24);
+
+    let colors: Vec<[f32; 4]> = mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)
+        .and_then(|positions| positions.as_float3())
+        .unwrap_or_default()
+        .iter()
+        .map(|[_, y, _]| {
+            // most of the change close to the horizon
+            let height = (y / SKY_RADIUS).max(0.0).sqrt();
+            horizon.mix(&zenith, height).to_linear().to_f32_array()
+        })
+        .collect();
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh
+}
+
+fn setup(
+    mut commands: Commands,
+    environment: Res<Environment>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Sky::Gradient { horizon, zenith } = environment.sky else {
+        return;
+    };
+
+    commands.spawn((
+        SkyDome,
+        Mesh3d(meshes.add(sky_dome_mesh(srgb(horizon), srgb(zenith)))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            unlit: true,
+            fog_enabled: false,
+            // seen from the inside
+            cull_mode: None,
+            ..default()
+        })),
+        NotShadowCaster,
+        Pickable::IGNORE,
+    ));
+}
+
+///
+/// Add the fog, atmosphere and environment map to new cameras.
+///
+fn configure_camera(
+    mut commands: Commands,
+    environment: Res<Environment>,
+    asset_server: Res<AssetServer>,
+    mut cameras: Query<(Entity, &mut Camera), Added<Camera3d>>,
+) {
+    for (entity_id, mut camera) in cameras.iter_mut() {
+        let mut entity = commands.entity(entity_id);
+
+        if let Some(fog) = &environment.fog {
+            entity.insert(DistanceFog {
+                color: srgb(fog.color),
+                falloff: FogFalloff::from_visibility(fog.visibility),
+                ..default()
+            });
+        }
+
+        if let Sky::Atmosphere = environment.sky {
+            // the atmosphere is only rendered by HDR cameras
+            camera.hdr = true;
+            entity.insert(Atmosphere::EARTH);
+        }
+
+        if let Some(map) = &environment.environment_map {
+            entity.insert(EnvironmentMapLight {
+                diffuse_map: asset_server.load(&map.diffuse),
+                specular_map: asset_server.load(&map.specular),
+                intensity: map.intensity,
+                ..default()
+            });
+        }
+    }
+}
+
+/// Camera's position, and the surroundings it shows.
+type CameraSurroundings = (
+    &'static GlobalTransform,
+    Option<&'static mut DistanceFog>,
+    Option<&'static mut EnvironmentMapLight>,
+);
+
+///
+/// Darken the sky, fog and reflections at night, and
+/// keep the sky dome centered on the camera.
+///
+fn update(
+    timeline: Res<Timeline>,
+    sun: Res<Sun>,
+    environment: Res<Environment>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut domes: Query<(&MeshMaterial3d<StandardMaterial>, &mut Transform), With<SkyDome>>,
+    mut cameras: Query<CameraSurroundings, With<Camera3d>>,
+) {
+    let brightness = sun.sky_brightness(timeline.now());
+
+    let Ok((camera_transform, fog, map)) = cameras.single_mut() else {
+        return;
+    };
+
+    for (material, mut transform) in domes.iter_mut() {
+        transform.translation = camera_transform.translation();
+
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color = NIGHT_TINT.mix(&Color::WHITE, brightness);
+        }
+    }
+
+    if let (Some(mut fog), Some(config)) = (fog, &environment.fog) {
+        fog.color = NIGHT_TINT.mix(&srgb(config.color), brightness);
+    }
+
+    if let (Some(mut map), Some(config)) = (map, &environment.environment_map) {
+        map.intensity = config.intensity * brightness;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scene_file_parses() {
+        let environment: Environment = ron::from_str(include_str!("../assets/scene.ron")).unwrap();
+
+        assert!(matches!(environment.sky, Sky::Gradient { .. }));
+        assert!(environment.fog.is_some());
+    }
+
+    #[test]
+    fn missing_fields_take_defaults() {
+        let environment: Environment = ron::from_str("Environment(sky: Atmosphere)").unwrap();
+
+        assert!(matches!(environment.sky, Sky::Atmosphere));
+        assert!(environment.fog.is_some());
+        assert!(environment.environment_map.is_none());
+    }
+}
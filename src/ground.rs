@@ -0,0 +1,238 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+
+use crate::bindings::{Action, Controls};
+
+pub struct GroundPlugin;
+
+impl Plugin for GroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ground>()
+            .add_systems(Startup, setup)
+            .add_systems(Update, toggle_see_through);
+    }
+}
+
+/// Width and depth of the terrain.
+const GROUND_SIZE: f32 = 80.0;
+
+/// Number of quads along each side of the terrain mesh.
+const RESOLUTION: usize = 160;
+
+/// Horizontal distance, from a trunk, at which the ground under it is sampled.
+const TRUNK_FOOTPRINT: f32 = 0.4;
+
+/// How far trunks lean with the slope of the ground,
+/// from 0.0 standing upright to 1.0 square to the ground.
+const TRUNK_LEAN: f32 = 0.25;
+
+/// Color of the ground in hollows.
+const HOLLOW_COLOR: Color = Color::srgb(0.20, 0.28, 0.10);
+
+/// Color of the ground on hilltops.
+const HILL_COLOR: Color = Color::srgb(0.42, 0.48, 0.22);
+
+/// Opacity of the ground, when seeing through it to the roots.
+const SEE_THROUGH_ALPHA: f32 = 0.3;
+
+/// The terrain's mesh.
+#[derive(Component)]
+struct Terrain;
+
+///
+/// Gently rolling terrain, defined by a height function.
+///
+#[derive(Resource, Debug)]
+pub struct Ground {
+    /// height difference, from the lowest hollow to the highest hilltop
+    pub relief: f32,
+    /// distance between neighbouring hilltops
+    pub wavelength: f32,
+}
+
+impl Default for Ground {
+    fn default() -> Self {
+        Ground {
+            relief: 1.6,
+            wavelength: 30.0,
+        }
+    }
+}
+
+impl Ground {
+    ///
+    /// Height of the ground at horizontal 'position'.
+    ///
+    pub fn height_at(&self, position: Vec2) -> f32 {
+        let p = position * std::f32::consts::TAU / self.wavelength;
+
+        //
+        // a few octaves of sines, at angles to each other
+        // so that the hills don't line up on a grid
+        //
+        let height = 0.5 * (p.x.sin() * (p.y * 0.8).cos())
+            + 0.3 * ((p.x * 0.6 + p.y * 1.7 + 1.3).sin())
+            + 0.2 * ((p.x * 2.3 - p.y * 1.9 + 0.4).sin());
+
+        height * self.relief / 2.0
+    }
+
+    ///
+    /// Height to plant a tree at, such that its trunk
+    /// reaches into the ground even on a slope.
+    ///
+    pub fn planting_height(&self, position: Vec2) -> f32 {
+        [Vec2::ZERO, Vec2::X, Vec2::NEG_X, Vec2::Y, Vec2::NEG_Y]
+            .into_iter()
+            .map(|offset| self.height_at(position + offset * TRUNK_FOOTPRINT))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    ///
+    /// Upward normal of the ground at horizontal 'position',
+    /// averaged over the footprint of a trunk.
+    ///
+    pub fn normal_at(&self, position: Vec2) -> Vec3 {
+        let slope = |offset: Vec2| {
+            self.height_at(position + offset * TRUNK_FOOTPRINT)
+                - self.height_at(position - offset * TRUNK_FOOTPRINT)
+        };
+
+        Vec3::new(-slope(Vec2::X), 2.0 * TRUNK_FOOTPRINT, -slope(Vec2::Y)).normalize()
+    }
+
+    ///
+    /// Where to plant a tree at horizontal 'position', with its
+    /// trunk leaning a little with the slope of the ground.
+    ///
+    pub fn planting_transform(&self, position: Vec2) -> Transform {
+        let up = Vec3::Y
+            .lerp(self.normal_at(position), TRUNK_LEAN)
+            .normalize();
+
+        Transform::from_xyz(position.x, self.planting_height(position), position.y)
+            .with_rotation(Quat::from_rotation_arc(Vec3::Y, up))
+    }
+
+    fn mesh(&self) -> Mesh {
+        let step = GROUND_SIZE / RESOLUTION as f32;
+        let vertices_per_side = RESOLUTION + 1;
+
+        let mut positions: Vec<[f32; 3]> = Vec::with_capacity(vertices_per_side.pow(2));
+        let mut colors: Vec<[f32; 4]> = Vec::with_capacity(vertices_per_side.pow(2));
+
+        for row in 0..vertices_per_side {
+            for column in 0..vertices_per_side {
+                let position = Vec2::new(column as f32, row as f32) * step - GROUND_SIZE / 2.0;
+                let height = self.height_at(position);
+
+                positions.push([position.x, height, position.y]);
+
+                // hilltops in lighter green
+                let hilliness = (height / self.relief + 0.5).clamp(0.0, 1.0);
+                colors.push(
+                    HOLLOW_COLOR
+                        .mix(&HILL_COLOR, hilliness)
+                        .to_linear()
+                        .to_f32_array(),
+                );
+            }
+        }
+
+        let mut indices: Vec<u32> = Vec::with_capacity(RESOLUTION.pow(2) * 6);
+        for row in 0..RESOLUTION {
+            for column in 0..RESOLUTION {
+                let corner = (row * vertices_per_side + column) as u32;
+                let below = corner + vertices_per_side as u32;
+
+                indices.extend([corner, below, corner + 1, corner + 1, below, below + 1]);
+            }
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices));
+        mesh.compute_normals();
+
+        mesh
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    ground: Res<Ground>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        Terrain,
+        Mesh3d(meshes.add(ground.mesh())),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            perceptual_roughness: 1.0,
+            ..default()
+        })),
+    ));
+}
+
+///
+/// Make the ground semi-transparent, showing the roots
+/// growing underground, or opaque again.
+///
+fn toggle_see_through(
+    controls: Controls,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    terrain: Query<&MeshMaterial3d<StandardMaterial>, With<Terrain>>,
+) {
+    if !controls.just_pressed(Action::SeeThroughGround) {
+        return;
+    }
+
+    for material in terrain.iter() {
+        let Some(material) = materials.get_mut(material) else {
+            continue;
+        };
+
+        if material.alpha_mode == AlphaMode::Opaque {
+            material.alpha_mode = AlphaMode::Blend;
+            material.base_color.set_alpha(SEE_THROUGH_ALPHA);
+        } else {
+            material.alpha_mode = AlphaMode::Opaque;
+            material.base_color.set_alpha(1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_ground_points_up() {
+        let ground = Ground {
+            relief: 0.0,
+            ..default()
+        };
+
+        assert_eq!(ground.normal_at(Vec2::new(3.0, -2.0)), Vec3::Y);
+    }
+
+    #[test]
+    fn trunks_lean_partly_with_the_slope() {
+        let ground = Ground::default();
+
+        for position in [Vec2::new(4.0, 1.0), Vec2::new(-7.5, 3.0)] {
+            let normal = ground.normal_at(position);
+            let up = ground.planting_transform(position).rotation * Vec3::Y;
+
+            assert!(up.angle_between(Vec3::Y) < normal.angle_between(Vec3::Y));
+            assert!(up.angle_between(normal) < normal.angle_between(Vec3::Y));
+        }
+    }
+}
@@ -5,22 +5,113 @@ use std::time::Duration;
 use bevy::time::common_conditions::on_timer;
 
 mod assets;
+mod season;
 mod tree;
-use tree::branch;
+use tree::{branch, competition, death, export, falling, inspector, leaf, pruning, roots};
+
+mod bindings;
+use bindings::BindingsPlugin;
+
+mod bookmarks;
+use bookmarks::BookmarksPlugin;
 
 mod camera;
 use camera::CameraPlugin;
 
+mod editor;
+use editor::EditorPlugin;
+
+mod environment;
+use environment::EnvironmentPlugin;
+
+mod ground;
+use ground::GroundPlugin;
+
+mod obstacles;
+use obstacles::ObstaclesPlugin;
+
+mod stats;
+use stats::StatsPlugin;
+
+mod sun;
+use sun::SunPlugin;
+
+mod timeline;
+use timeline::TimelinePlugin;
+
+mod tools;
+use tools::{Tool, ToolsPlugin};
+
+mod wind;
+use wind::WindPlugin;
+
 const TREE_UPDATE_TIME: u64 = 1200;
 
 fn main() {
     App::new()
-        .add_plugins((DefaultPlugins, CameraPlugin))
-        .add_systems(Startup, (tree::setup, assets::setup))
+        .add_plugins((
+            DefaultPlugins,
+            BindingsPlugin,
+            BookmarksPlugin,
+            CameraPlugin,
+            EditorPlugin,
+            EnvironmentPlugin,
+            GroundPlugin,
+            ObstaclesPlugin,
+            StatsPlugin,
+            SunPlugin,
+            TimelinePlugin,
+            ToolsPlugin,
+            WindPlugin,
+        ))
+        .add_systems(Startup, (tree::setup, assets::setup, inspector::setup))
+        .add_systems(
+            Update,
+            (
+                leaf::tint_materials,
+                leaf::update,
+                leaf::flutter,
+                falling::update,
+                branch::sway,
+                tree::hide_unborn,
+                inspector::show,
+                tree::regrow,
+                export::export,
+            ),
+        )
+        .add_systems(
+            Update,
+            pruning::cut
+                .run_if(resource_equals(Tool::Prune))
+                .run_if(timeline::is_live),
+        )
+        .add_systems(
+            Update,
+            inspector::select.run_if(resource_equals(Tool::Inspect)),
+        )
+        .add_systems(
+            Update,
+            (
+                tree::sense_obstacles,
+                tree::update,
+                competition::compete,
+                roots::grow,
+                branch::spawn_leafs,
+                death::starve,
+                death::shed,
+            )
+                .run_if(on_timer(Duration::from_millis(TREE_UPDATE_TIME)))
+                .run_if(timeline::is_live),
+        )
+        //
+        // while the timeline is rewound, branches
+        // are re-meshed as the time shown changes
+        //
         .add_systems(
             Update,
-            (tree::update, branch::update, branch::spawn_leafs)
-                .run_if(on_timer(Duration::from_millis(TREE_UPDATE_TIME))),
+            branch::update.run_if(
+                on_timer(Duration::from_millis(TREE_UPDATE_TIME)).or(not(timeline::is_live)),
+            ),
         )
         .run();
 }
@@ -0,0 +1,185 @@
+use bevy::{
+    math::{
+        Affine3A,
+        bounding::{Aabb3d, RayCast3d},
+    },
+    prelude::*,
+    render::primitives::Aabb,
+};
+
+use crate::ground::Ground;
+
+pub struct ObstaclesPlugin;
+
+impl Plugin for ObstaclesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup);
+    }
+}
+
+/// Size of the wall placed behind the tree.
+const WALL_SIZE: Vec3 = Vec3::new(8.0, 5.0, 0.3);
+
+/// Distance from the trunk to the face of the wall.
+const WALL_DISTANCE: f32 = 0.8;
+
+/// Size of the box placed beside the tree.
+const BOX_SIZE: Vec3 = Vec3::new(1.5, 1.5, 1.5);
+
+/// Position of the box's center, relative to the trunk's base.
+const BOX_OFFSET: Vec3 = Vec3::new(2.0, 3.0, 0.5);
+
+/// Color of the obstacles.
+const OBSTACLE_COLOR: Color = Color::srgb(0.55, 0.52, 0.48);
+
+///
+/// Static mesh branches grow around, rather than through.
+///
+/// Any entity with a mesh can be made an obstacle, its
+/// bounding box being what branches keep out of.
+///
+#[derive(Component)]
+pub struct Obstacle;
+
+///
+/// Bounding box of an obstacle, oriented along its axes.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Bounds {
+    /// from world coordinates to the obstacle's own
+    local_from_world: Affine3A,
+    /// the box, in the obstacle's own coordinates
+    aabb: Aabb3d,
+}
+
+impl Bounds {
+    pub fn new(transform: &GlobalTransform, aabb: &Aabb) -> Self {
+        Bounds {
+            local_from_world: transform.affine().inverse(),
+            aabb: Aabb3d::new(aabb.center, aabb.half_extents),
+        }
+    }
+
+    ///
+    /// Distance from 'origin' along 'direction' to the obstacle,
+    /// or infinity when the obstacle is not in the way.
+    ///
+    pub fn clearance(&self, origin: Vec3, direction: Dir3) -> f32 {
+        //
+        // cast the ray in the obstacle's coordinates, where
+        // its bounds are axis aligned, scaling the distance
+        // back should the obstacle be scaled
+        //
+        let scaled = self.local_from_world.transform_vector3(*direction).into();
+        let Ok(local_direction) = Dir3A::new(scaled) else {
+            return f32::INFINITY;
+        };
+        let origin = self.local_from_world.transform_point3a(origin.into());
+
+        RayCast3d::new(origin, local_direction, f32::INFINITY)
+            .aabb_intersection_at(&self.aabb)
+            .map_or(f32::INFINITY, |distance| distance / scaled.length())
+    }
+}
+
+///
+/// Distance from 'origin' along 'direction' to the nearest obstacle.
+///
+pub fn clearance(obstacles: &[Bounds], origin: Vec3, direction: Dir3) -> f32 {
+    obstacles
+        .iter()
+        .map(|obstacle| obstacle.clearance(origin, direction))
+        .fold(f32::INFINITY, f32::min)
+}
+
+///
+/// Place obstacles next to the tree, a wall behind it and a box
+/// beside it, as named by the command line arguments following
+/// the species, e.g. 'wald maple wall box'.
+///
+fn setup(
+    mut commands: Commands,
+    ground: Res<Ground>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let base = Vec3::new(0.0, ground.planting_height(Vec2::ZERO), 0.0);
+
+    // sunk into the ground, not to float over hollows
+    let sunk = Vec3::Y * ground.relief / 2.0;
+
+    for arg in std::env::args().skip(2) {
+        let (size, center) = match arg.as_str() {
+            "wall" => {
+                let offset = Vec3::new(0.0, WALL_SIZE.y / 2.0, -WALL_DISTANCE - WALL_SIZE.z / 2.0);
+                (WALL_SIZE + sunk, base + offset - sunk / 2.0)
+            }
+            "box" => (BOX_SIZE, base + BOX_OFFSET),
+            _ => {
+                warn!("unknown obstacle '{arg}'");
+                continue;
+            }
+        };
+
+        commands.spawn((
+            Obstacle,
+            Mesh3d(meshes.add(Cuboid::from_size(size))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: OBSTACLE_COLOR,
+                perceptual_roughness: 0.9,
+                ..default()
+            })),
+            Transform::from_translation(center),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box(transform: Transform) -> Bounds {
+        let aabb = Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5));
+        Bounds::new(&GlobalTransform::from(transform), &aabb)
+    }
+
+    #[test]
+    fn distance_to_face_in_the_way() {
+        let obstacle = unit_box(Transform::from_xyz(3.0, 0.0, 0.0));
+
+        let distance = obstacle.clearance(Vec3::ZERO, Dir3::X);
+        assert!((distance - 2.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn clear_when_not_in_the_way() {
+        let obstacle = unit_box(Transform::from_xyz(3.0, 0.0, 0.0));
+
+        assert_eq!(obstacle.clearance(Vec3::ZERO, Dir3::NEG_X), f32::INFINITY);
+        assert_eq!(obstacle.clearance(Vec3::ZERO, Dir3::Y), f32::INFINITY);
+    }
+
+    #[test]
+    fn scaled_and_rotated_obstacle() {
+        let obstacle = unit_box(
+            Transform::from_xyz(0.0, 0.0, 5.0)
+                .with_rotation(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2))
+                .with_scale(Vec3::new(4.0, 1.0, 1.0)),
+        );
+
+        // stretched along x, then turned to stretch along z
+        let distance = obstacle.clearance(Vec3::ZERO, Dir3::Z);
+        assert!((distance - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn nearest_obstacle_counts() {
+        let obstacles = [
+            unit_box(Transform::from_xyz(0.0, 6.0, 0.0)),
+            unit_box(Transform::from_xyz(0.0, 4.0, 0.0)),
+        ];
+
+        let distance = clearance(&obstacles, Vec3::ZERO, Dir3::Y);
+        assert!((distance - 3.5).abs() < 1e-5);
+    }
+}
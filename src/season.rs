@@ -0,0 +1,31 @@
+/// Seconds of growth time per simulated year.
+pub const YEAR_LENGTH: f32 = 120.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    ///
+    /// Season at the specified growth time, together with
+    /// how far into the season we are, from 0.0 to 1.0.
+    ///
+    /// Each year starts with spring.
+    ///
+    pub fn at(now: f32) -> (Season, f32) {
+        let quarters = (now / YEAR_LENGTH).fract() * 4.0;
+
+        let season = match quarters as u32 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        };
+
+        (season, quarters.fract())
+    }
+}
@@ -0,0 +1,203 @@
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use std::collections::HashMap;
+
+use crate::bindings::{Action, Controls};
+use crate::season::YEAR_LENGTH;
+use crate::timeline::Timeline;
+use crate::tree::branch::Branch;
+use crate::tree::falling::Falling;
+use crate::tree::inspector::Selected;
+use crate::tree::leaf::Leaf;
+use crate::tree::roots::{InCrown, Root};
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FrameTimeDiagnosticsPlugin::default())
+            .add_systems(Startup, setup)
+            .add_systems(Update, (toggle_overlay, update_overlay));
+    }
+}
+
+/// On-screen overlay with statistics of the trees, and of the rendering.
+#[derive(Component)]
+struct StatsOverlay;
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        StatsOverlay,
+        Text::default(),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            right: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Visibility::Hidden,
+    ));
+}
+
+fn toggle_overlay(controls: Controls, mut overlay: Query<&mut Visibility, With<StatsOverlay>>) {
+    if !controls.just_pressed(Action::ToggleStats) {
+        return;
+    }
+
+    for mut visibility in overlay.iter_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+///
+/// Bounds and size of a tree's crown, from the
+/// bases and tips of the branches on the tree.
+///
+struct Crown {
+    min: Vec3,
+    max: Vec3,
+    branches: usize,
+    total_length: f32,
+    age: f32,
+}
+
+impl Default for Crown {
+    fn default() -> Self {
+        Crown {
+            min: Vec3::INFINITY,
+            max: Vec3::NEG_INFINITY,
+            branches: 0,
+            total_length: 0.0,
+            age: 0.0,
+        }
+    }
+}
+
+impl Crown {
+    fn add(&mut self, branch: &Branch, transform: &GlobalTransform, now: f32) {
+        let base = transform.translation();
+        let tip = branch.tip(transform, now);
+
+        self.min = self.min.min(base).min(tip);
+        self.max = self.max.max(base).max(tip);
+        self.branches += 1;
+        self.total_length += branch.length(now);
+
+        if branch.order() == 0 {
+            self.age = self.age.max(branch.age(now));
+        }
+    }
+
+    fn size(&self) -> Vec3 {
+        (self.max - self.min).max(Vec3::ZERO)
+    }
+}
+
+///
+/// Show the statistics of the tree a branch is selected
+/// on, or else of the tallest tree, as shown on the timeline.
+///
+fn update_overlay(
+    timeline: Res<Timeline>,
+    diagnostics: Res<DiagnosticsStore>,
+    meshes: Res<Assets<Mesh>>,
+    branches: Query<(&Branch, &GlobalTransform, Has<Selected>), InCrown>,
+    roots: Query<&Branch, With<Root>>,
+    leaves: Query<&Leaf, Without<Falling>>,
+    mut overlay: Query<(&mut Text, &Visibility), With<StatsOverlay>>,
+) {
+    let Ok((mut text, visibility)) = overlay.single_mut() else {
+        return;
+    };
+
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    let now = timeline.now();
+
+    // leave out whatever was born after the time shown
+    let mut crowns: HashMap<Entity, Crown> = HashMap::new();
+    let mut selected = None;
+
+    for (branch, transform, is_selected) in branches.iter() {
+        if branch.birth_time() > now {
+            continue;
+        }
+
+        crowns
+            .entry(branch.tree())
+            .or_default()
+            .add(branch, transform, now);
+
+        if is_selected {
+            selected = Some(branch.tree());
+        }
+    }
+
+    let tallest = crowns
+        .iter()
+        .max_by(|(_, a), (_, b)| a.size().y.total_cmp(&b.size().y))
+        .map(|(tree, _)| *tree);
+    let Some(tree) = selected.or(tallest) else {
+        text.0 = "no trees".into();
+        return;
+    };
+    let crown = &crowns[&tree];
+    let size = crown.size();
+
+    let roots: Vec<&Branch> = roots
+        .iter()
+        .filter(|root| root.tree() == tree && root.birth_time() <= now)
+        .collect();
+    let root_length: f32 = roots.iter().map(|root| root.length(now)).sum();
+    let leaves = leaves
+        .iter()
+        .filter(|leaf| leaf.tree() == tree && leaf.birth_time() <= now)
+        .count();
+
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or_default();
+    let frame_time = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame_time| frame_time.smoothed())
+        .unwrap_or_default();
+
+    text.0 = format!(
+        "trees: {}\n\
+         branches: {}\n\
+         leaves: {}\n\
+         height: {:.2}\n\
+         crown width: {:.2}\n\
+         total branch length: {:.1}\n\
+         roots: {} ({:.1} long)\n\
+         age: {:.1} years\n\
+         fps: {:.0} ({:.1} ms)\n\
+         meshes: {}",
+        crowns.len(),
+        crown.branches,
+        leaves,
+        size.y,
+        size.x.max(size.z),
+        crown.total_length,
+        roots.len(),
+        root_length,
+        crown.age / YEAR_LENGTH,
+        fps,
+        frame_time,
+        meshes.len(),
+    );
+}
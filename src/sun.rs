@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+use crate::season::YEAR_LENGTH;
+use crate::timeline::Timeline;
+
+pub struct SunPlugin;
+
+impl Plugin for SunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Sun>()
+            .add_systems(Startup, setup)
+            .add_systems(Update, update);
+    }
+}
+
+/// Tilt of the earth's axis, in radians, giving the sun's swing over the year.
+const AXIAL_TILT: f32 = 0.409;
+
+/// Illuminance of the sun, high in the sky.
+const NOON_ILLUMINANCE: f32 = light_consts::lux::AMBIENT_DAYLIGHT;
+
+/// Sun elevation, in radians, above which it shines at full strength.
+const FULL_DAYLIGHT_ELEVATION: f32 = 0.3;
+
+/// Color of the sun when rising and setting.
+const SUNRISE_COLOR: Color = Color::srgb(1.0, 0.6, 0.35);
+
+/// Sky colors, at night, at sunrise and sunset, and in daylight.
+const NIGHT_SKY: Color = Color::srgb(0.02, 0.03, 0.08);
+const SUNRISE_SKY: Color = Color::srgb(0.85, 0.55, 0.45);
+const DAY_SKY: Color = Color::srgb(0.45, 0.65, 0.9);
+
+/// Ambient light brightness, at night and in daylight.
+const NIGHT_AMBIENT: f32 = 30.0;
+const DAY_AMBIENT: f32 = 500.0;
+
+/// Ambient light colors, at night and in daylight.
+const NIGHT_AMBIENT_COLOR: Color = Color::srgb(0.5, 0.6, 1.0);
+const DAY_AMBIENT_COLOR: Color = Color::srgb(0.9, 0.95, 1.0);
+
+///
+/// The sun's course over the sky, through the day and the year.
+///
+/// Each year starts at the spring equinox, and each day at midnight.
+///
+#[derive(Resource, Debug)]
+pub struct Sun {
+    /// latitude, in radians, where the trees grow, negative to the south
+    pub latitude: f32,
+    /// seconds of growth time per day
+    pub day_length: f32,
+}
+
+impl Default for Sun {
+    fn default() -> Self {
+        Sun {
+            latitude: 0.87,
+            day_length: 40.0,
+        }
+    }
+}
+
+impl Sun {
+    ///
+    /// Time of day at 'now', from 0.0 at midnight
+    /// through 0.5 at noon, up to 1.0.
+    ///
+    pub fn time_of_day(&self, now: f32) -> f32 {
+        (now / self.day_length).fract()
+    }
+
+    ///
+    /// Direction towards the sun, at 'now'.
+    ///
+    /// North is towards -Z, and east towards +X.
+    ///
+    pub fn direction(&self, now: f32) -> Vec3 {
+        let declination = AXIAL_TILT * ((now / YEAR_LENGTH).fract() * TAU).sin();
+        let hour_angle = (self.time_of_day(now) - 0.5) * TAU;
+
+        let (sin_latitude, cos_latitude) = self.latitude.sin_cos();
+        let (sin_declination, cos_declination) = declination.sin_cos();
+
+        let up = sin_latitude * sin_declination + cos_latitude * cos_declination * hour_angle.cos();
+        let east = -cos_declination * hour_angle.sin();
+        let north =
+            cos_latitude * sin_declination - sin_latitude * cos_declination * hour_angle.cos();
+
+        Vec3::new(east, up, -north)
+    }
+
+    ///
+    /// Angle, in radians, of the sun above the horizon at 'now'.
+    ///
+    pub fn elevation(&self, now: f32) -> f32 {
+        self.direction(now).y.clamp(-1.0, 1.0).asin()
+    }
+
+    ///
+    /// Strength of the sunlight, at 'now', from 0.0
+    /// at night up to 1.0 with the sun high in the sky.
+    ///
+    pub fn daylight(&self, now: f32) -> f32 {
+        (self.elevation(now) / FULL_DAYLIGHT_ELEVATION).clamp(0.0, 1.0)
+    }
+
+    ///
+    /// Brightness of the sky at 'now', from 0.0 at night up to
+    /// 1.0 in daylight, already brightening before sunrise.
+    ///
+    pub fn sky_brightness(&self, now: f32) -> f32 {
+        (self.elevation(now) / FULL_DAYLIGHT_ELEVATION * 0.5 + 0.5).clamp(0.0, 1.0)
+    }
+
+    ///
+    /// Direction towards the sun at 'now', scaled
+    /// by the strength of the sunlight.
+    ///
+    pub fn sunlight(&self, now: f32) -> Vec3 {
+        self.direction(now) * self.daylight(now)
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        DirectionalLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::default(),
+    ));
+}
+
+///
+/// Move the sun over the sky, changing the
+/// color of the sky and ambient light with it.
+///
+fn update(
+    timeline: Res<Timeline>,
+    sun: Res<Sun>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut clear_color: ResMut<ClearColor>,
+    mut lights: Query<(&mut DirectionalLight, &mut Transform)>,
+) {
+    let now = timeline.now();
+    let direction = sun.direction(now);
+    // in units of the elevation of full daylight
+    let elevation = sun.elevation(now) / FULL_DAYLIGHT_ELEVATION;
+    let daylight = sun.daylight(now);
+
+    for (mut light, mut transform) in lights.iter_mut() {
+        *transform = Transform::default().looking_to(-direction, Vec3::Y);
+        light.illuminance = NOON_ILLUMINANCE * daylight;
+        light.color = SUNRISE_COLOR.mix(&Color::WHITE, daylight);
+    }
+
+    //
+    // the sky glows around sunrise and sunset, while
+    // the sun is just below or above the horizon
+    //
+    let twilight = 1.0 - elevation.abs().min(1.0);
+    let sky = NIGHT_SKY.mix(&DAY_SKY, daylight);
+    clear_color.0 = sky.mix(&SUNRISE_SKY, twilight * 0.6);
+
+    let brightness = sun.sky_brightness(now);
+    ambient_light.brightness = NIGHT_AMBIENT + (DAY_AMBIENT - NIGHT_AMBIENT) * brightness;
+    ambient_light.color = NIGHT_AMBIENT_COLOR.mix(&DAY_AMBIENT_COLOR, brightness);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    ///
+    /// Noon at the spring equinox.
+    ///
+    fn equinox_noon() -> (Sun, f32) {
+        let sun = Sun {
+            day_length: 48.0,
+            ..default()
+        };
+        let noon = YEAR_LENGTH * 3.0;
+        assert_eq!(sun.time_of_day(noon), 0.5);
+
+        (sun, noon)
+    }
+
+    #[test]
+    fn noon_sun_stands_in_the_south() {
+        let (sun, noon) = equinox_noon();
+
+        let direction = sun.direction(noon);
+        assert!(direction.x.abs() < 1e-3);
+        assert!(direction.z > 0.0);
+        assert!((sun.elevation(noon) - (FRAC_PI_2 - sun.latitude)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sun_rises_in_the_east() {
+        let (sun, noon) = equinox_noon();
+
+        let morning = sun.direction(noon - sun.day_length * 0.2);
+        let evening = sun.direction(noon + sun.day_length * 0.2);
+        assert!(morning.x > 0.0);
+        assert!(evening.x < 0.0);
+    }
+
+    #[test]
+    fn no_sunlight_at_night() {
+        let (sun, noon) = equinox_noon();
+        let midnight = noon + sun.day_length / 2.0;
+
+        assert!(sun.elevation(midnight) < 0.0);
+        assert_eq!(sun.daylight(midnight), 0.0);
+        assert_eq!(sun.sunlight(midnight), Vec3::ZERO);
+    }
+}
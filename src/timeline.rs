@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+
+use crate::bindings::{self, Action, Controls};
+
+pub struct TimelinePlugin;
+
+impl Plugin for TimelinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Timeline>().add_systems(
+            PreUpdate,
+            (toggle_playing.after(bindings::capture_binding), advance).chain(),
+        );
+    }
+}
+
+///
+/// Simulated time the trees grow in.
+///
+/// The timeline can be rewound, to show the trees as they looked
+/// earlier in their life. Since branch lengths are a function of time,
+/// the trees can be recomputed for any earlier time, hiding whatever
+/// was born later. Leaves that have dropped, and branches that have
+/// broken off, are gone from the tree for good though, they're
+/// only hidden from the ground before the time they dropped.
+///
+/// Trees only grow while the timeline is live, that is when
+/// it's not rewound. Played back from an earlier time, the
+/// timeline runs forward until it catches up with the live time.
+///
+#[derive(Resource, Debug)]
+pub struct Timeline {
+    /// simulated seconds the trees have grown for
+    live: f32,
+    /// simulated time shown, earlier than 'live' when rewound
+    view: f32,
+    /// simulated seconds the live time advanced in the last frame
+    delta: f32,
+    /// whether the time runs
+    pub playing: bool,
+    /// simulated seconds per real second
+    pub speed: f32,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Timeline {
+            live: 0.0,
+            view: 0.0,
+            delta: 0.0,
+            playing: true,
+            speed: 1.0,
+        }
+    }
+}
+
+impl Timeline {
+    ///
+    /// The simulated time shown.
+    ///
+    pub fn now(&self) -> f32 {
+        self.view
+    }
+
+    pub fn delta_secs(&self) -> f32 {
+        self.delta
+    }
+
+    ///
+    /// The latest simulated time the trees have grown to.
+    ///
+    pub fn live(&self) -> f32 {
+        self.live
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.view >= self.live
+    }
+
+    ///
+    /// Show the trees at an earlier time, up to the live time.
+    ///
+    pub fn scrub(&mut self, time: f32) {
+        self.view = time.clamp(0.0, self.live);
+    }
+
+    pub fn go_live(&mut self) {
+        self.view = self.live;
+    }
+}
+
+///
+/// Run condition for systems simulating growth, which
+/// must not run while the timeline is rewound.
+///
+pub fn is_live(timeline: Res<Timeline>) -> bool {
+    timeline.is_live()
+}
+
+fn toggle_playing(controls: Controls, mut timeline: ResMut<Timeline>) {
+    if controls.just_pressed(Action::PlayPause) {
+        timeline.playing = !timeline.playing;
+    }
+}
+
+fn advance(time: Res<Time>, mut timeline: ResMut<Timeline>) {
+    timeline.delta = 0.0;
+
+    if !timeline.playing {
+        return;
+    }
+
+    let dt = time.delta_secs() * timeline.speed;
+
+    if timeline.is_live() {
+        timeline.live += dt;
+        timeline.view = timeline.live;
+        timeline.delta = dt;
+    } else {
+        // play back towards the live time
+        timeline.view = (timeline.view + dt).min(timeline.live);
+    }
+}
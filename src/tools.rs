@@ -0,0 +1,111 @@
+use bevy::{picking::mesh_picking::MeshPickingPlugin, prelude::*};
+
+use crate::bindings::{Action, Controls};
+use crate::tree::branch::Branch;
+
+/// Pointer movement, in pixels, from press to release above
+/// which a click is taken as a drag of the camera.
+const MAX_CLICK_DISTANCE: f32 = 5.0;
+
+pub struct ToolsPlugin;
+
+impl Plugin for ToolsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MeshPickingPlugin)
+            .init_resource::<Tool>()
+            .init_resource::<PressPosition>()
+            .add_event::<BranchClicked>()
+            .add_systems(Update, select_tool)
+            .add_observer(pointer_pressed)
+            .add_observer(pointer_clicked);
+    }
+}
+
+///
+/// The tool used when clicking on the tree.
+///
+#[derive(Resource, Default, Clone, Copy, PartialEq, Debug)]
+pub enum Tool {
+    /// clicks are left to the camera controls
+    #[default]
+    None,
+    /// cut off clicked branches
+    Prune,
+    /// select clicked branches, and show their properties
+    Inspect,
+}
+
+impl Tool {
+    ///
+    /// Action that selects the tool, or deselects it if already selected.
+    ///
+    fn action(&self) -> Option<Action> {
+        match self {
+            Tool::None => None,
+            Tool::Prune => Some(Action::PruneTool),
+            Tool::Inspect => Some(Action::InspectTool),
+        }
+    }
+}
+
+///
+/// A branch, or one of its leaves, has been clicked with a tool.
+///
+#[derive(Event, Debug)]
+pub struct BranchClicked {
+    pub branch: Entity,
+}
+
+/// Where the pointer was last pressed.
+#[derive(Resource, Default)]
+struct PressPosition(Vec2);
+
+fn select_tool(controls: Controls, mut tool: ResMut<Tool>) {
+    let Some(pressed) = [Tool::Prune, Tool::Inspect].into_iter().find(|tool| {
+        tool.action()
+            .is_some_and(|action| controls.just_pressed(action))
+    }) else {
+        return;
+    };
+
+    *tool = if *tool == pressed {
+        Tool::None
+    } else {
+        pressed
+    };
+
+    info!("selected tool {:?}", *tool);
+}
+
+fn pointer_pressed(trigger: Trigger<Pointer<Pressed>>, mut press_position: ResMut<PressPosition>) {
+    press_position.0 = trigger.pointer_location.position;
+}
+
+fn pointer_clicked(
+    mut trigger: Trigger<Pointer<Click>>,
+    tool: Res<Tool>,
+    press_position: Res<PressPosition>,
+    branches: Query<(), With<Branch>>,
+    mut clicks: EventWriter<BranchClicked>,
+) {
+    if *tool == Tool::None || trigger.button != PointerButton::Primary {
+        return;
+    }
+
+    //
+    // clicks on leaves propagate up to the
+    // branch they are growing on
+    //
+    let target = trigger.target();
+    if !branches.contains(target) {
+        return;
+    }
+    trigger.propagate(false);
+
+    if trigger.pointer_location.position.distance(press_position.0) > MAX_CLICK_DISTANCE {
+        // the camera was dragged
+        return;
+    }
+
+    clicks.write(BranchClicked { branch: target });
+}
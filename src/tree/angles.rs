@@ -1,138 +1,153 @@
-use std::f32::consts::TAU;
+use std::f32::consts::{PI, TAU};
 
+use bevy::prelude::*;
 use rand::Rng;
 use rand::rngs::SmallRng;
 
-const RANGE_WIDHT: f32 = 2.0;
-const BELL_WIDTH: f32 = 0.6;
+use super::parameters::Parameters;
 
-fn overflow_below_0(angle: f32) -> Option<f32> {
-    let overflow = (RANGE_WIDHT / 2.0) - angle;
-    if overflow <= 0.0 {
-        return None;
-    }
+/// Free length, in the direction of a new branch, it needs to grow into.
+const MIN_CLEARANCE: f32 = 1.0;
 
-    Some(TAU - overflow)
-}
+/// Obstructed directions sampled, before settling for the least obstructed one.
+const OBSTRUCTED_SAMPLES: usize = 50;
 
-fn overflow_above_tau(angle: f32) -> Option<f32> {
-    let overflow = (angle + (RANGE_WIDHT / 2.0)) - TAU;
-    if overflow <= 0.0 {
-        return None;
-    }
-
-    Some(overflow)
+///
+/// Direction of a branch, relative to the axis of its parent.
+///
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BranchAngle {
+    /// rotation around the parent's axis
+    pub azimuth: f32,
+    /// angle between the branch and the parent's axis
+    pub inclination: f32,
 }
 
-fn wraped_values(angle: f32, x: f32) -> (f32, f32) {
-    let diff = (x - angle).abs();
+impl BranchAngle {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.azimuth) * Quat::from_rotation_z(self.inclination)
+    }
 
-    if diff < (RANGE_WIDHT / 2.0) {
-        /* no wrapping needed */
-        return (angle, x);
+    pub fn direction(&self) -> Vec3 {
+        self.rotation() * Vec3::Y
     }
 
-    if angle < x {
-        /* wrap around angle full-circle */
-        (angle + TAU, x)
-    } else {
-        assert!(x < angle);
-        /* wrap around x full-circle */
-        (angle, x + TAU)
+    ///
+    /// Angular distance, in radians, between the directions of two branches.
+    ///
+    fn distance(&self, other: &BranchAngle) -> f32 {
+        self.direction().angle_between(other.direction())
     }
 }
 
 ///
 /// Angle Probability Density Function (PDF)
 ///
-fn angle_pdf(angle: f32, x: f32) -> f32 {
+fn angle_pdf(distance: f32, bell_width: f32) -> f32 {
     //
     // implements guassian distribution
-    // with the top at 'angle' and hard-coded 'width'
+    // with the top at zero distance and 'bell_width' width
     //
-
-    // take care of cases where angle 'bell' wraps around 0 or TAU
-    let (wraped_x, wraped_angle) = wraped_values(angle, x);
-
-    ((-(wraped_x - wraped_angle).powi(2)) / BELL_WIDTH).exp()
+    ((-distance.powi(2)) / bell_width).exp()
 }
 
-pub fn find_in_range_angle(angles: &Vec<f32>, new_angle: f32) -> Option<f32> {
-    let mut in_range_angle: Option<(f32, f32)> = None;
-
-    let mut set_in_range_angle = |diff: f32, angle: f32| {
-        in_range_angle = match in_range_angle {
-            // no in range angle yet, save this one
-            None => Some((diff, angle)),
-            // update with new angle _if_
-            // new one is closer then the old one
-            Some((curr_diff, _)) => {
-                if curr_diff < diff {
-                    in_range_angle
-                } else {
-                    Some((diff, angle))
-                }
-            }
-        }
-    };
+///
+/// Find the branch angle closest to 'new_angle', among the
+/// ones that are within 'range_width' on the sphere.
+///
+pub fn find_in_range_angle(
+    angles: &[BranchAngle],
+    new_angle: &BranchAngle,
+    range_width: f32,
+) -> Option<BranchAngle> {
+    let mut in_range_angle: Option<(f32, BranchAngle)> = None;
 
     for angle in angles.iter() {
-        let diff = (new_angle - angle).abs();
-
-        if diff <= (RANGE_WIDHT / 2.0) {
-            set_in_range_angle(diff, *angle);
-        }
+        let diff = new_angle.distance(angle);
 
-        // deal with angles that wrap around 0.0
-        match overflow_below_0(new_angle) {
-            Some(overflow) => {
-                if overflow < *angle {
-                    set_in_range_angle(diff, *angle);
-                    return Some(*angle);
-                }
-            }
-            None => { /* nop */ }
+        if diff > (range_width / 2.0) {
+            continue;
         }
 
-        // deal with angles that wrap around tau
-        match overflow_above_tau(new_angle) {
-            Some(overflow) => {
-                if *angle < overflow {
-                    set_in_range_angle(diff, *angle);
-                    return Some(*angle);
-                }
-            }
-            None => { /* nop */ }
-        }
+        in_range_angle = match in_range_angle {
+            // update with new angle _if_
+            // new one is closer then the old one
+            Some((curr_diff, _)) if curr_diff < diff => in_range_angle,
+            _ => Some((diff, *angle)),
+        };
     }
 
-    match in_range_angle {
-        None => None,
-        Some((_, angle)) => Some(angle),
-    }
+    in_range_angle.map(|(_, angle)| angle)
 }
 
-fn accept(branch_angles: &Vec<f32>, new_angle: f32, y: f32) -> bool {
-    let in_range_angle = find_in_range_angle(branch_angles, new_angle);
+fn accept(
+    branch_angles: &[BranchAngle],
+    new_angle: &BranchAngle,
+    y: f32,
+    parameters: &Parameters,
+) -> bool {
+    let in_range_angle = find_in_range_angle(branch_angles, new_angle, parameters.range_width);
 
-    if in_range_angle.is_none() {
+    let Some(in_range_angle) = in_range_angle else {
         // nothing in range, accept
         return true;
-    }
+    };
 
-    let probability = angle_pdf(in_range_angle.unwrap(), new_angle);
+    let probability = angle_pdf(new_angle.distance(&in_range_angle), parameters.bell_width);
 
     // note that our PDF is 'inverted'
     y > probability
 }
 
-pub fn new_branch_angle(branch_angles: &Vec<f32>, rng: &mut SmallRng) -> f32 {
+///
+/// Sample a new branch direction on the sphere.
+///
+/// The azimuth is uniformly distributed, while the inclination
+/// is spread by up to 'jitter' around the specified 'inclination'.
+/// Directions close to existing branches are likely to be rejected.
+///
+/// Directions with less than 'MIN_CLEARANCE' free length, as
+/// measured by 'clearance', are rejected too. Returns the sampled
+/// direction, together with its free length.
+///
+pub fn new_branch_angle(
+    branch_angles: &[BranchAngle],
+    inclination: f32,
+    jitter: f32,
+    parameters: &Parameters,
+    rng: &mut SmallRng,
+    clearance: impl Fn(&BranchAngle) -> f32,
+) -> (BranchAngle, f32) {
+    let mut least_obstructed: Option<(BranchAngle, f32)> = None;
+    let mut obstructed = 0;
+
     loop {
-        let angle = rng.random_range(0.0..TAU);
+        let angle = BranchAngle {
+            azimuth: rng.random_range(0.0..TAU),
+            inclination: (inclination + rng.random_range(-1.0..1.0) * jitter).clamp(0.0, PI),
+        };
         let y = rng.random_range(0.0..1.0);
 
-        if accept(branch_angles, angle, y) {
-            return angle;
+        if !accept(branch_angles, &angle, y, parameters) {
+            continue;
+        }
+
+        let free_length = clearance(&angle);
+        if free_length >= MIN_CLEARANCE {
+            return (angle, free_length);
+        }
+
+        //
+        // hemmed in on all sides, grow
+        // where there is most space left
+        //
+        if least_obstructed.is_none_or(|(_, most)| free_length > most) {
+            least_obstructed = Some((angle, free_length));
+        }
+
+        obstructed += 1;
+        if obstructed == OBSTRUCTED_SAMPLES {
+            return least_obstructed.unwrap();
         }
     }
 }
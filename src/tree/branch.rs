@@ -3,52 +3,231 @@ use bevy::math::u32;
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology};
 use core::f32;
-use std::f32::consts::{PI, TAU};
+use rand::Rng;
+use std::f32::consts::TAU;
 
-const SEGMENTS: usize = 5;
-const LEAF_SPACING: f32 = 0.24;
-const LENGTH_RATIO: f32 = 1.5;
+/// Length of what remains of a branch after it breaks off.
+const STUB_LENGTH: f32 = 0.12;
 
+/// How much branches bend in the wind.
+const SWAY_FACTOR: f32 = 0.0001;
+
+/// Maximum bend of a branch in the wind, in radians.
+const MAX_SWAY: f32 = 0.4;
+
+/// Sway frequency, in Hz, of a branch of unit length.
+const SWAY_FREQUENCY: f32 = 0.6;
+
+/// Vigor is rounded to steps of 1 / 'VIGOR_STEPS', keeping growth histories short.
+const VIGOR_STEPS: f32 = 10.0;
+
+use super::Tree;
+use super::angles::BranchAngle;
+use super::falling::{Falling, FallingAncestry};
+use super::growth::GrowthCurve;
+use super::leaf::{self, Leaf};
+use super::roots::Root;
 use crate::assets::LoadedAssets;
+use crate::season::Season;
+use crate::timeline::Timeline;
+use crate::wind::Wind;
 
 #[derive(Component, Debug)]
 pub struct Branch {
     birth_time: f32,
+    growth_curve: GrowthCurve,
     growth_rate: f32,
-    leaf_pairs: u32,
+    order: u32,
+    angle: BranchAngle,
+    tree: Entity,
+    /// length the branch grows up to, before running into an obstacle
+    reach: f32,
+    leaf_nodes: u32,
+    /// growth vigor, and the time since when it applies, in chronological order
+    vigor: Vec<(f32, f32)>,
+    starving_since: Option<f32>,
+    death_time: Option<f32>,
 }
 
 impl Branch {
-    pub fn new(now: f32, growth_rate: f32) -> Self {
+    pub fn new(
+        now: f32,
+        growth_curve: GrowthCurve,
+        growth_rate: f32,
+        order: u32,
+        angle: BranchAngle,
+        tree: Entity,
+    ) -> Self {
         Branch {
             birth_time: now,
-            growth_rate: growth_rate,
-            leaf_pairs: 0,
+            growth_curve,
+            growth_rate,
+            order,
+            angle,
+            tree,
+            reach: f32::INFINITY,
+            leaf_nodes: 0,
+            vigor: vec![(now, 1.0)],
+            starving_since: None,
+            death_time: None,
         }
     }
 
-    fn age(&self, now: f32) -> f32 {
+    ///
+    /// Stop the branch from growing longer than 'reach'.
+    ///
+    pub fn with_reach(mut self, reach: f32) -> Self {
+        self.reach = reach;
+        self
+    }
+
+    ///
+    /// Number of branchings between the trunk and this branch,
+    /// the trunk itself is of order 0.
+    ///
+    pub fn order(&self) -> u32 {
+        self.order
+    }
+
+    pub fn tree(&self) -> Entity {
+        self.tree
+    }
+
+    pub fn birth_time(&self) -> f32 {
+        self.birth_time
+    }
+
+    pub fn growth_rate(&self) -> f32 {
+        self.growth_rate
+    }
+
+    pub fn angle(&self) -> BranchAngle {
+        self.angle
+    }
+
+    ///
+    /// Number of nodes along the branch with leaves on them.
+    ///
+    pub fn leaf_nodes(&self) -> u32 {
+        self.leaf_nodes
+    }
+
+    pub fn age(&self, now: f32) -> f32 {
         now - self.birth_time
     }
 
+    ///
+    /// Set how vigorously the branch grows from now on,
+    /// 1.0 being the pace of its growth curve.
+    ///
+    pub fn set_vigor(&mut self, now: f32, vigor: f32) {
+        let vigor = (vigor * VIGOR_STEPS).round() / VIGOR_STEPS;
+
+        if self.vigor.last().is_some_and(|(_, last)| *last == vigor) {
+            return;
+        }
+
+        self.vigor.push((now, vigor));
+    }
+
+    ///
+    /// Age the branch would have reached growing at full vigor
+    /// all its life, to grow as long as it has until 'now'.
+    ///
+    fn growth_age(&self, now: f32) -> f32 {
+        self.vigor
+            .iter()
+            .enumerate()
+            .map(|(n, (since, vigor))| {
+                let until = self.vigor.get(n + 1).map_or(now, |(until, _)| *until);
+
+                (until.min(now) - since).max(0.0) * vigor
+            })
+            .sum()
+    }
+
+    ///
+    /// Whether the branch had died by 'now'.
+    ///
+    pub fn is_dead(&self, now: f32) -> bool {
+        self.death_time.is_some_and(|death_time| death_time <= now)
+    }
+
+    ///
+    /// Seconds since the branch died, None for living branches.
+    ///
+    pub fn time_dead(&self, now: f32) -> Option<f32> {
+        self.death_time.map(|death_time| now - death_time)
+    }
+
+    ///
+    /// Keep track of how long the branch has been starving,
+    /// returns true when it has starved to death.
+    ///
+    pub fn starve(&mut self, now: f32, starving: bool, starvation_time: f32) -> bool {
+        if !starving {
+            self.starving_since = None;
+            return false;
+        }
+
+        let starving_since = *self.starving_since.get_or_insert(now);
+        if now - starving_since < starvation_time {
+            return false;
+        }
+
+        self.die(now);
+        true
+    }
+
+    ///
+    /// Stop the branch from growing, unless it's already dead.
+    ///
+    pub fn die(&mut self, now: f32) {
+        self.death_time.get_or_insert(now);
+    }
+
     pub fn length(&self, now: f32) -> f32 {
-        let age = self.age(now);
+        // dead branches don't grow
+        let now = self
+            .death_time
+            .map_or(now, |death_time| now.min(death_time));
 
-        let length = if age <= 1.64 {
-            return age * 0.3 * LENGTH_RATIO;
-        } else {
-            age.ln() * LENGTH_RATIO
-        };
+        // not born yet when the timeline is rewound, age is 0
+        let length = self.growth_curve.length(self.growth_age(now)) * self.growth_rate;
+
+        // stops short of obstacles
+        length.min(self.reach)
+    }
 
-        length * self.growth_rate
+    ///
+    /// Position of the branch's tip, in world coordinates.
+    ///
+    pub fn tip(&self, transform: &GlobalTransform, now: f32) -> Vec3 {
+        transform.transform_point(Vec3::Y * self.length(now))
     }
 
-    pub fn expected_leaf_pairs(&self, now: f32) -> u32 {
-        ((self.length(now) - LEAF_SPACING * 0.2) / LEAF_SPACING) as u32
+    ///
+    /// Radius of the branch at it's base.
+    ///
+    pub fn thickness(&self, now: f32) -> f32 {
+        thickness(self.length(now))
     }
 
-    pub fn get_mesh(&self, now: f32) -> Mesh {
-        create_mesh(self.length(now))
+    pub fn expected_leaf_nodes(&self, now: f32, leaf_spacing: f32) -> u32 {
+        ((self.length(now) - leaf_spacing * 0.2) / leaf_spacing) as u32
+    }
+
+    pub fn get_mesh(&self, now: f32, segments: usize) -> Mesh {
+        let length = self.length(now);
+
+        create_mesh(length, thickness(length), segments)
+    }
+
+    ///
+    /// Mesh for the stub left on the parent, when the branch breaks off.
+    ///
+    pub fn get_stub_mesh(&self, now: f32, segments: usize) -> Mesh {
+        create_mesh(STUB_LENGTH, self.thickness(now), segments)
     }
 }
 
@@ -82,10 +261,12 @@ fn triangle_indices(num: usize) -> Indices {
     Indices::U32(indices)
 }
 
-fn create_mesh(length: f32) -> Mesh {
-    let base_scale = (length.ln() / 10.0).clamp(0.05, f32::INFINITY);
+fn thickness(length: f32) -> f32 {
+    (length.ln() / 10.0).clamp(0.05, f32::INFINITY)
+}
 
-    let mut vertices = base_vertices(SEGMENTS, base_scale);
+fn create_mesh(length: f32, base_scale: f32, segments: usize) -> Mesh {
+    let mut vertices = base_vertices(segments, base_scale);
     vertices.push([0.0, length, 0.0]);
 
     // not sure about this, be seems to work OK
@@ -99,7 +280,7 @@ fn create_mesh(length: f32) -> Mesh {
     )
     .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vertices)
     .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, norms)
-    .with_inserted_indices(triangle_indices(SEGMENTS))
+    .with_inserted_indices(triangle_indices(segments))
 }
 
 pub fn spawn_new(
@@ -107,16 +288,13 @@ pub fn spawn_new(
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     now: f32,
-    growth_rate: f32,
+    branch: Branch,
     height: f32,
-    y_rotation: f32,
-    z_rotation: f32,
+    segments: usize,
 ) -> Entity {
-    let branch = Branch::new(now, growth_rate);
-    let cube_mesh_handle: Handle<Mesh> = meshes.add(branch.get_mesh(now));
+    let cube_mesh_handle: Handle<Mesh> = meshes.add(branch.get_mesh(now, segments));
 
-    let trans = Transform::from_xyz(0.0, height, 0.0)
-        .with_rotation(Quat::from_rotation_y(y_rotation) * Quat::from_rotation_z(z_rotation));
+    let trans = Transform::from_xyz(0.0, height, 0.0).with_rotation(branch.angle.rotation());
 
     commands
         .spawn((
@@ -130,20 +308,37 @@ pub fn spawn_new(
 
 pub fn update(
     mut commands: Commands,
-    time: Res<Time>,
+    timeline: Res<Timeline>,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut branches: Query<(Entity, &mut Branch)>,
+    trees: Query<&Tree>,
+    branches: Query<(Entity, &Branch)>,
+    ancestry: FallingAncestry,
+    mut last_update: Local<f32>,
 ) {
-    let now = time.elapsed_secs();
+    let now = timeline.now();
+    let last_update = std::mem::replace(&mut *last_update, now);
+
+    for (entity_id, branch) in branches.iter() {
+        //
+        // dead branches, or ones stopped by an obstacle, keep their
+        // length, unless the timeline is rewound to before they stopped
+        //
+        if branch.length(last_update) == branch.length(now) || ancestry.is_falling(entity_id) {
+            continue;
+        }
+
+        // the tree may have been regrown, leaving this branch behind
+        let Ok(tree) = trees.get(branch.tree) else {
+            continue;
+        };
 
-    for (entity_id, branch) in branches.iter_mut() {
         /*
          * grow branch
          *
          * replace it's old mesh with new one
          * with updated dimensions
          */
-        let mesh_handle: Handle<Mesh> = meshes.add(branch.get_mesh(now));
+        let mesh_handle: Handle<Mesh> = meshes.add(branch.get_mesh(now, tree.parameters.segments));
         commands
             .entity(entity_id)
             .remove::<Mesh3d>()
@@ -151,48 +346,194 @@ pub fn update(
     }
 }
 
+///
+/// Bend branches in the wind.
+///
+/// Long and thin branches bend more, and sway slower, than short
+/// and thick ones. Each branch bends relative to its parent, thus
+/// the motion propagates from the trunk out to the outermost twigs.
+///
+pub fn sway(
+    timeline: Res<Timeline>,
+    wind: Res<Wind>,
+    mut branches: Query<(&Branch, &mut Transform, &ChildOf), Without<Root>>,
+    parents: Query<&GlobalTransform>,
+) {
+    let now = timeline.now();
+    let speed = wind.speed(now);
+
+    for (branch, mut transform, child_of) in branches.iter_mut() {
+        let length = branch.length(now);
+        if length <= 0.0 {
+            continue;
+        }
+
+        let bend = (speed * SWAY_FACTOR * length / branch.thickness(now).powi(2)).min(MAX_SWAY);
+
+        // wobble around the bent position
+        let frequency = SWAY_FREQUENCY / length.sqrt();
+        let wobble = 0.7 + 0.3 * (now * frequency * TAU + branch.birth_time).sin();
+
+        //
+        // the axis to bend around, tilting the branch
+        // along the wind, in parent's coordinates
+        //
+        let parent_rotation = parents
+            .get(child_of.parent())
+            .map_or(Quat::IDENTITY, |parent| parent.rotation());
+        let axis = parent_rotation.inverse() * wind.direction.cross(Vec3::NEG_Y);
+
+        transform.rotation = Quat::from_axis_angle(axis.normalize_or(Vec3::X), bend * wobble)
+            * branch.angle.rotation();
+    }
+}
+
+///
+/// Nodes, up to 'expected_nodes', none of the leaves at 'leaf_nodes' grow at.
+///
+fn bare_nodes(expected_nodes: u32, leaf_nodes: impl IntoIterator<Item = u32>) -> Vec<u32> {
+    let mut bare = vec![true; expected_nodes as usize];
+    for node in leaf_nodes {
+        if let Some(bare) = bare.get_mut(node as usize) {
+            *bare = false;
+        }
+    }
+
+    (0..expected_nodes)
+        .filter(|node| bare[*node as usize])
+        .collect()
+}
+
+///
+/// Sprout leaves at the nodes of growing branches, and
+/// at nodes whose leaves have all dropped.
+///
 pub fn spawn_leafs(
     mut commands: Commands,
-    time: Res<Time>,
+    timeline: Res<Timeline>,
     assets: Res<LoadedAssets>,
-    mut branches: Query<(Entity, &mut Branch)>,
+    mut trees: Query<&mut Tree>,
+    mut branches: Query<(Entity, &mut Branch, Option<&Children>), Without<Root>>,
+    leaves: Query<&Leaf, Without<Falling>>,
+    ancestry: FallingAncestry,
 ) {
-    let now = time.elapsed_secs();
+    let now = timeline.now();
 
-    let right_leaf_rot = Quat::from_rotation_z(-0.5);
-    let left_leaf_rot = right_leaf_rot * Quat::from_rotation_y(PI);
+    let (season, _) = Season::at(now);
 
-    for (entity_id, mut branch) in branches.iter_mut() {
-        let expected_pairs = branch.expected_leaf_pairs(now);
+    for (entity_id, mut branch, children) in branches.iter_mut() {
+        if branch.is_dead(now) || ancestry.is_falling(entity_id) {
+            continue;
+        }
 
-        while branch.leaf_pairs < expected_pairs {
-            let leaf_height = ((branch.leaf_pairs + 1) as f32) * LEAF_SPACING;
+        let Ok(mut tree) = trees.get_mut(branch.tree) else {
+            continue;
+        };
 
-            //
-            // spawn 'right' leaf
-            //
-            let leaf = commands
-                .spawn((
-                    Mesh3d(assets.leaf_mesh.clone()),
-                    MeshMaterial3d(assets.leaf_material.clone()),
-                    Transform::from_xyz(0.0, leaf_height, 0.0).with_rotation(right_leaf_rot),
-                ))
-                .id();
-            commands.entity(entity_id).add_child(leaf);
+        let leaf_spacing = tree.parameters.leaf_spacing;
+        let expected_nodes = branch.expected_leaf_nodes(now, leaf_spacing);
+
+        //
+        // dropped leaves are detached from the branch,
+        // their nodes are bare until leaves sprout again
+        //
+        let leaf_nodes = children
+            .into_iter()
+            .flatten()
+            .filter_map(|child| leaves.get(*child).ok())
+            .map(Leaf::node);
+        let bare_nodes = bare_nodes(expected_nodes, leaf_nodes);
+        branch.leaf_nodes = expected_nodes - bare_nodes.len() as u32;
+
+        // deciduous trees bud again in spring
+        if tree.species.deciduous && matches!(season, Season::Autumn | Season::Winter) {
+            continue;
+        }
+
+        for node in bare_nodes.iter().copied() {
+            let leaf_height = ((node + 1) as f32) * leaf_spacing;
 
             //
-            // spawn 'left' leaf
+            // spawn all leafs of the node,
+            // arranged according to tree's species
             //
-            let leaf = commands
-                .spawn((
-                    Mesh3d(assets.leaf_mesh.clone()),
-                    MeshMaterial3d(assets.leaf_material.clone()),
-                    Transform::from_xyz(0.0, leaf_height, 0.0).with_rotation(left_leaf_rot),
-                ))
-                .id();
-            commands.entity(entity_id).add_child(leaf);
-
-            branch.leaf_pairs += 1;
+            for leaf_rot in tree.get_leaf_rotations(node) {
+                let hardiness = tree.rng.random_range(0.0..1.0);
+                let leaf = commands
+                    .spawn(leaf::new_bundle(
+                        &assets,
+                        now,
+                        branch.tree,
+                        node,
+                        hardiness,
+                        Transform::from_xyz(0.0, leaf_height, 0.0).with_rotation(leaf_rot),
+                    ))
+                    .id();
+                commands.entity(entity_id).add_child(leaf);
+            }
         }
+
+        branch.leaf_nodes = expected_nodes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::species::Species;
+
+    #[test]
+    fn sprouts_leaves_at_bare_nodes() {
+        assert_eq!(bare_nodes(4, []), [0, 1, 2, 3]);
+        assert_eq!(bare_nodes(4, [0, 0, 2, 7]), [1, 3]);
+    }
+
+    #[test]
+    fn evergreens_grow_leaves_back() {
+        let species = Species::oleander();
+        assert!(!species.deciduous);
+
+        // leaves of the first nodes grew before the others
+        let leaves: Vec<Leaf> = (0..4)
+            .map(|node| Leaf::new(node as f32, Entity::PLACEHOLDER, node, 0.5, Quat::IDENTITY))
+            .collect();
+
+        let now = species.leaf_lifespan + 1.5;
+        let on_branch = leaves
+            .iter()
+            .filter(|leaf| !leaf.is_dropping(&species, now))
+            .map(Leaf::node);
+        let bare = bare_nodes(4, on_branch);
+        assert_eq!(bare, [0, 1]);
+
+        // sprouted again, the branch is fully leafed
+        let regrown: Vec<Leaf> = bare
+            .iter()
+            .map(|node| Leaf::new(now, Entity::PLACEHOLDER, *node, 0.5, Quat::IDENTITY))
+            .collect();
+        let on_branch = leaves
+            .iter()
+            .chain(&regrown)
+            .filter(|leaf| !leaf.is_dropping(&species, now))
+            .map(Leaf::node);
+        assert!(bare_nodes(4, on_branch).is_empty());
+    }
+    #[test]
+    fn rewound_dead_branches_are_alive() {
+        let curve = GrowthCurve::LinearLog { scale: 1.0 };
+        let mut branch = Branch::new(
+            0.0,
+            curve,
+            1.0,
+            1,
+            BranchAngle::default(),
+            Entity::PLACEHOLDER,
+        );
+        branch.die(10.0);
+
+        assert!(branch.is_dead(10.0));
+        assert!(!branch.is_dead(5.0));
+        assert_eq!(branch.length(20.0), branch.length(10.0));
+        assert!(branch.length(5.0) < branch.length(10.0));
     }
 }
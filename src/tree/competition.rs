@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+use super::Tree;
+use super::branch::Branch;
+use super::light;
+use super::roots::InCrown;
+use crate::sun::Sun;
+use crate::timeline::Timeline;
+
+/// Vigor of branches deep in the shade.
+const MIN_VIGOR: f32 = 0.2;
+
+/// Largest share of new branches a tree gives up growing towards its neighbours.
+const MAX_CROWDING: f32 = 0.9;
+
+///
+/// Let trees compete for light and space.
+///
+/// Branches in the shade, of their own crown or of taller
+/// neighbours, grow slower. Trees whose crowns overlap avoid
+/// sprouting new branches towards each other, closing the
+/// canopy instead of growing through it, while leaning
+/// towards the sun where it shines from.
+///
+pub fn compete(
+    timeline: Res<Timeline>,
+    sun: Res<Sun>,
+    mut trees: Query<(Entity, &mut Tree, &GlobalTransform)>,
+    mut branches: Query<(&mut Branch, &GlobalTransform), InCrown>,
+) {
+    let now = timeline.now();
+
+    let crowns = light::crowns(
+        trees
+            .iter()
+            .map(|(entity_id, _, transform)| (entity_id, transform)),
+        branches.iter(),
+        now,
+    );
+
+    for (mut branch, transform) in branches.iter_mut() {
+        if branch.is_dead(now) {
+            continue;
+        }
+
+        let exposure = light::exposure_at(&crowns, branch.tree(), branch.tip(transform, now));
+        branch.set_vigor(now, exposure.max(MIN_VIGOR));
+    }
+
+    for (entity_id, mut tree, _) in trees.iter_mut() {
+        let crown = crowns[&entity_id];
+
+        //
+        // lean away from neighbours, the more
+        // the crowns overlap, the stronger
+        //
+        let crowding: Vec2 = crowns
+            .iter()
+            .filter(|(other_id, _)| **other_id != entity_id)
+            .map(|(_, other)| {
+                let offset = other.center - crown.center;
+                let reach = crown.radius + other.radius;
+                if reach <= 0.0 {
+                    return Vec2::ZERO;
+                }
+
+                let overlap = (reach - offset.length()) / reach;
+                offset.normalize_or_zero() * overlap.max(0.0)
+            })
+            .sum();
+
+        tree.crowding = crowding.clamp_length_max(MAX_CROWDING);
+        tree.sunlight = sun.sunlight(now);
+    }
+}
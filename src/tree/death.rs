@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+
+use super::Tree;
+use super::branch::Branch;
+use super::falling::Falling;
+use super::leaf::{self, Leaf};
+use super::light;
+use super::roots::InCrown;
+use crate::timeline::Timeline;
+
+/// Seconds a branch survives without enough light.
+const STARVATION_TIME: f32 = 20.0;
+
+/// Seconds a dead branch stays on the tree, before it breaks off.
+const DECAY_TIME: f32 = 30.0;
+
+/// Color of dead, dried out, branches.
+const DRY_COLOR: Color = Color::srgb(0.42, 0.36, 0.30);
+
+///
+/// Let branches that don't get enough light starve to death,
+/// together with everything growing on them.
+///
+/// As the crown rises, the lower branches end up in the shade
+/// of the ones above, or of neighbouring trees, and die off.
+///
+pub fn starve(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    trees: Query<(Entity, &Tree, &GlobalTransform)>,
+    mut branches: Query<
+        (
+            Entity,
+            &mut Branch,
+            &GlobalTransform,
+            &MeshMaterial3d<StandardMaterial>,
+        ),
+        InCrown,
+    >,
+    children: Query<&Children>,
+    leaves: Query<&Leaf, Without<Falling>>,
+) {
+    let now = timeline.now();
+
+    let crowns = light::crowns(
+        trees
+            .iter()
+            .map(|(entity_id, _, transform)| (entity_id, transform)),
+        branches
+            .iter()
+            .map(|(_, branch, transform, _)| (branch, transform)),
+        now,
+    );
+
+    let mut starved = vec![];
+
+    for (entity_id, mut branch, transform, _) in branches.iter_mut() {
+        // the trunk never starves
+        if branch.order() == 0 || branch.is_dead(now) {
+            continue;
+        }
+
+        let Ok((_, tree, _)) = trees.get(branch.tree()) else {
+            continue;
+        };
+        let exposure = light::exposure_at(&crowns, branch.tree(), branch.tip(transform, now));
+        let starving = exposure < tree.species.shade_tolerance;
+
+        if branch.starve(now, starving, STARVATION_TIME) {
+            starved.push(entity_id);
+        }
+    }
+
+    //
+    // dry out, along with the shoots growing
+    // on it, and drop all leaves
+    //
+    for entity_id in starved {
+        let shoots = children.iter_descendants(entity_id);
+
+        for entity_id in std::iter::once(entity_id).chain(shoots) {
+            if let Ok(leaf) = leaves.get(entity_id) {
+                leaf::drop_leaf(&mut commands, now, entity_id, leaf);
+                continue;
+            }
+
+            let Ok((_, mut branch, _, material)) = branches.get_mut(entity_id) else {
+                continue;
+            };
+            branch.die(now);
+            if let Some(material) = materials.get_mut(material) {
+                material.base_color = DRY_COLOR;
+            }
+        }
+    }
+}
+
+type DeadBranch<'a> = (
+    Entity,
+    &'a Branch,
+    &'a Transform,
+    &'a ChildOf,
+    &'a MeshMaterial3d<StandardMaterial>,
+);
+
+///
+/// Break off branches that have been dead for a while,
+/// leaving a stub on the parent.
+///
+/// Dead shoots on a dead branch stay on it, and break off with it.
+///
+pub fn shed(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut trees: Query<&mut Tree>,
+    branches: Query<DeadBranch, Without<Falling>>,
+    parents: Query<&Branch>,
+) {
+    let now = timeline.now();
+
+    for (entity_id, branch, transform, child_of, material) in branches.iter() {
+        match branch.time_dead(now) {
+            Some(time_dead) if time_dead >= DECAY_TIME => { /* break off */ }
+            _ => continue,
+        }
+
+        if parents
+            .get(child_of.parent())
+            .is_ok_and(|parent| parent.is_dead(now))
+        {
+            continue;
+        }
+
+        let Ok(mut tree) = trees.get_mut(branch.tree()) else {
+            continue;
+        };
+        tree.forget_branch(child_of.parent(), entity_id);
+
+        let stub = commands
+            .spawn((
+                Mesh3d(meshes.add(branch.get_stub_mesh(now, tree.parameters.segments))),
+                material.clone(),
+                *transform,
+            ))
+            .id();
+        commands.entity(child_of.parent()).add_child(stub);
+
+        commands
+            .entity(entity_id)
+            .remove_parent_in_place()
+            .insert(Falling::branch(now, branch.birth_time().fract()));
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use super::Tree;
+
+/// File trees are exported to.
+const EXPORT_PATH: &str = "tree.obj";
+
+///
+/// Export all trees, as they currently look, to a Wavefront OBJ file.
+///
+#[derive(Event)]
+pub struct Export;
+
+///
+/// Write meshes, transformed to world coordinates, as OBJ objects.
+///
+fn write_obj<'a>(
+    path: &str,
+    parts: impl Iterator<Item = (&'a Mesh, &'a GlobalTransform)>,
+) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+
+    // OBJ vertex indices are global, and start at 1
+    let mut first_vertex = 1;
+
+    for (n, (mesh, transform)) in parts.enumerate() {
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            continue;
+        };
+
+        writeln!(file, "o part{n}")?;
+
+        for position in positions {
+            let vertex = transform.transform_point(Vec3::from(*position));
+            writeln!(file, "v {} {} {}", vertex.x, vertex.y, vertex.z)?;
+        }
+
+        if let Some(indices) = mesh.indices() {
+            let indices: Vec<usize> = indices.iter().collect();
+
+            for face in indices.chunks_exact(3) {
+                writeln!(
+                    file,
+                    "f {} {} {}",
+                    first_vertex + face[0],
+                    first_vertex + face[1],
+                    first_vertex + face[2]
+                )?;
+            }
+        }
+
+        first_vertex += positions.len();
+    }
+
+    file.flush()
+}
+
+pub fn export(
+    mut requests: EventReader<Export>,
+    meshes: Res<Assets<Mesh>>,
+    trees: Query<Entity, With<Tree>>,
+    children: Query<&Children>,
+    parts: Query<(&Mesh3d, &GlobalTransform)>,
+) {
+    if requests.read().count() == 0 {
+        return;
+    }
+
+    //
+    // branches, stubs and leaves still on the trees
+    //
+    let parts = trees
+        .iter()
+        .flat_map(|tree| children.iter_descendants(tree))
+        .filter_map(|part| parts.get(part).ok())
+        .filter_map(|(mesh, transform)| Some((meshes.get(mesh)?, transform)));
+
+    match write_obj(EXPORT_PATH, parts) {
+        Ok(()) => info!("exported trees to '{EXPORT_PATH}'"),
+        Err(err) => error!("failed to export trees to '{EXPORT_PATH}': {err}"),
+    }
+}
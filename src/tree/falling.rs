@@ -0,0 +1,236 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use crate::ground::Ground;
+use crate::timeline::Timeline;
+use crate::wind::Wind;
+
+/// Downward acceleration of falling objects.
+const GRAVITY: Vec3 = Vec3::new(0.0, -9.81, 0.0);
+
+/// How strongly the air drags a falling leaf towards its own velocity.
+const LEAF_DRAG: f32 = 6.0;
+
+/// How strongly the air drags a falling branch towards its own velocity.
+const BRANCH_DRAG: f32 = 0.3;
+
+/// How fast a falling branch tumbles, in radians per second.
+const TUMBLING_SPEED: f32 = 1.2;
+
+/// How much of the wind's velocity falling leaves drift with.
+const WIND_DRIFT: f32 = 0.4;
+
+/// Top speed of a leaf swinging from side to side.
+const FLUTTER_SPEED: f32 = 0.8;
+
+/// Side to side swings per second.
+const FLUTTER_FREQUENCY: f32 = 0.7;
+
+/// How far a leaf rocks around its axis while swinging, in radians per second.
+const ROCKING_SPEED: f32 = 2.5;
+
+/// Seconds landed objects lie on the ground before they start to fade out.
+const LYING_TIME: f32 = 40.0;
+
+/// Seconds it takes a landed object to fade out.
+const FADE_TIME: f32 = 5.0;
+
+#[derive(Debug)]
+enum Kind {
+    /// light, flutters down
+    Leaf,
+    /// heavy, tumbles down
+    Branch,
+}
+
+///
+/// Leaf, or broken off branch, falling from its tree.
+///
+#[derive(Component, Debug)]
+pub struct Falling {
+    kind: Kind,
+    /// when it dropped off the tree
+    drop_time: f32,
+    velocity: Vec3,
+    /// 0.0 to 1.0, varies the way the object falls
+    seed: f32,
+    landing: Option<Landing>,
+}
+
+#[derive(Debug)]
+struct Landing {
+    time: f32,
+    size: f32,
+}
+
+///
+/// Finds out whether an entity is part of
+/// something falling, e.g. a broken off branch.
+///
+#[derive(SystemParam)]
+pub struct FallingAncestry<'w, 's> {
+    parents: Query<'w, 's, &'static ChildOf>,
+    falling: Query<'w, 's, (), With<Falling>>,
+}
+
+impl FallingAncestry<'_, '_> {
+    ///
+    /// Whether 'entity', or anything it's attached to, is falling.
+    ///
+    pub fn is_falling(&self, entity: Entity) -> bool {
+        self.falling.contains(entity)
+            || self
+                .parents
+                .iter_ancestors(entity)
+                .any(|ancestor| self.falling.contains(ancestor))
+    }
+}
+
+impl Falling {
+    pub fn leaf(now: f32, seed: f32) -> Self {
+        Falling {
+            kind: Kind::Leaf,
+            drop_time: now,
+            velocity: Vec3::ZERO,
+            seed,
+            landing: None,
+        }
+    }
+
+    pub fn branch(now: f32, seed: f32) -> Self {
+        Falling {
+            kind: Kind::Branch,
+            drop_time: now,
+            velocity: Vec3::ZERO,
+            seed,
+            landing: None,
+        }
+    }
+
+    pub fn drop_time(&self) -> f32 {
+        self.drop_time
+    }
+
+    ///
+    /// Horizontal direction a leaf swings in,
+    /// and a branch tumbles towards.
+    ///
+    fn swing_direction(&self) -> Vec3 {
+        let angle = self.seed * TAU;
+
+        Vec3::new(angle.cos(), 0.0, angle.sin())
+    }
+
+    ///
+    /// Where in the swing the leaf is, from -1.0 to 1.0.
+    ///
+    fn swing(&self, now: f32) -> f32 {
+        (now * FLUTTER_FREQUENCY * TAU + self.seed * TAU).sin()
+    }
+}
+
+fn fall(
+    falling: &mut Falling,
+    transform: &mut Transform,
+    ground: &Ground,
+    wind: Vec3,
+    now: f32,
+    dt: f32,
+) {
+    let swing = falling.swing(now);
+    let swing_direction = falling.swing_direction();
+    let rotation_axis = swing_direction.cross(Vec3::Y);
+
+    //
+    // the air the object is falling through moves with the wind,
+    // leaf flutters by swinging from side to side in it
+    //
+    let (air_velocity, drag) = match falling.kind {
+        Kind::Leaf => (
+            wind * WIND_DRIFT + swing_direction * swing * FLUTTER_SPEED,
+            LEAF_DRAG,
+        ),
+        Kind::Branch => (wind * WIND_DRIFT, BRANCH_DRAG),
+    };
+    let drag = (air_velocity - falling.velocity) * drag;
+
+    falling.velocity += (GRAVITY + drag) * dt;
+    transform.translation += falling.velocity * dt;
+
+    let rotation = match falling.kind {
+        // rock around the axis across the swing
+        Kind::Leaf => swing * ROCKING_SPEED * dt,
+        Kind::Branch => TUMBLING_SPEED * dt,
+    };
+    transform.rotate(Quat::from_axis_angle(rotation_axis, rotation));
+
+    let ground_level = ground.height_at(transform.translation.xz());
+    if transform.translation.y > ground_level {
+        return;
+    }
+
+    let (yaw, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
+
+    match falling.kind {
+        Kind::Leaf => {
+            //
+            // land flat on the ground, slightly above leaves
+            // landed earlier, so that they pile up
+            //
+            transform.rotation = Quat::from_rotation_y(yaw);
+            transform.translation.y = ground_level + falling.seed * 0.01;
+        }
+        Kind::Branch => {
+            // lie down along the ground
+            transform.rotation = Quat::from_rotation_y(yaw) * Quat::from_rotation_z(-FRAC_PI_2);
+            transform.translation.y = ground_level;
+        }
+    }
+
+    falling.landing = Some(Landing {
+        time: now,
+        size: transform.scale.x,
+    });
+}
+
+///
+/// Returns false when the object has faded out completely.
+///
+fn lie(landing: &Landing, transform: &mut Transform, now: f32) -> bool {
+    let fading_time = now - landing.time - LYING_TIME;
+    if fading_time <= 0.0 {
+        return true;
+    }
+
+    let fade = 1.0 - fading_time / FADE_TIME;
+    if fade <= 0.0 {
+        return false;
+    }
+
+    transform.scale = Vec3::splat(landing.size * fade);
+
+    true
+}
+
+pub fn update(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    ground: Res<Ground>,
+    wind: Res<Wind>,
+    mut falling_objects: Query<(Entity, &mut Falling, &mut Transform)>,
+) {
+    let now = timeline.now();
+    let dt = timeline.delta_secs();
+    let wind = wind.velocity(now);
+
+    for (entity_id, mut falling, mut transform) in falling_objects.iter_mut() {
+        match &falling.landing {
+            None => fall(&mut falling, &mut transform, &ground, wind, now, dt),
+            Some(landing) => {
+                if !lie(landing, &mut transform, now) {
+                    commands.entity(entity_id).despawn();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::f32::consts::{SQRT_2, TAU};
+
+use super::parameters::Parameters;
+use super::plant;
+use super::species::Species;
+use crate::ground::Ground;
+
+/// Width and depth of the area a forest is planted in.
+const FOREST_SIZE: Vec2 = Vec2::new(24.0, 24.0);
+
+/// Least distance between two trees of a forest.
+const TREE_DISTANCE: f32 = 4.0;
+
+/// Candidate points tried around each point, before giving up on it.
+const CANDIDATES: usize = 30;
+
+///
+/// Sample points in a 'size' rectangle, centered on the origin,
+/// none closer than 'min_distance' to another, using Bridson's
+/// Poisson-disk sampling algorithm.
+///
+fn poisson_disk(size: Vec2, min_distance: f32, rng: &mut SmallRng) -> Vec<Vec2> {
+    //
+    // the grid's cells are small enough
+    // to hold at most one point each
+    //
+    let cell_size = min_distance / SQRT_2;
+    let columns = (size.x / cell_size).ceil() as usize;
+    let rows = (size.y / cell_size).ceil() as usize;
+    let cell = |point: Vec2| {
+        (
+            ((point.x / cell_size) as usize).min(columns - 1),
+            ((point.y / cell_size) as usize).min(rows - 1),
+        )
+    };
+    let cell_index = |point: Vec2| {
+        let (column, row) = cell(point);
+        row * columns + column
+    };
+
+    let first = Vec2::new(rng.random_range(0.0..size.x), rng.random_range(0.0..size.y));
+
+    let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+    grid[cell_index(first)] = Some(0);
+    let mut points: Vec<Vec2> = vec![first];
+    let mut active: Vec<usize> = vec![0];
+
+    while !active.is_empty() {
+        let index = rng.random_range(0..active.len());
+        let center = points[active[index]];
+
+        let candidate = (0..CANDIDATES)
+            .map(|_| {
+                let angle = rng.random_range(0.0..TAU);
+                let distance = rng.random_range(min_distance..2.0 * min_distance);
+                center + Vec2::from_angle(angle) * distance
+            })
+            .find(|candidate| {
+                if candidate.x < 0.0 || candidate.y < 0.0 {
+                    return false;
+                }
+                if candidate.x >= size.x || candidate.y >= size.y {
+                    return false;
+                }
+
+                // points close enough to conflict are at most two cells away
+                let (column, row) = cell(*candidate);
+                (row.saturating_sub(2)..(row + 3).min(rows)).all(|row| {
+                    (column.saturating_sub(2)..(column + 3).min(columns)).all(|column| {
+                        grid[row * columns + column]
+                            .is_none_or(|n| points[n].distance(*candidate) >= min_distance)
+                    })
+                })
+            });
+
+        match candidate {
+            Some(candidate) => {
+                grid[cell_index(candidate)] = Some(points.len());
+                active.push(points.len());
+                points.push(candidate);
+            }
+            None => {
+                active.swap_remove(index);
+            }
+        }
+    }
+
+    points.into_iter().map(|point| point - size / 2.0).collect()
+}
+
+///
+/// Plant a forest of trees of random species, each with
+/// its own seed, spread evenly over an area of the ground.
+///
+pub fn plant_forest(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    now: f32,
+    ground: &Ground,
+) {
+    let mut rng = SmallRng::seed_from_u64(Parameters::default().seed);
+
+    for position in poisson_disk(FOREST_SIZE, TREE_DISTANCE, &mut rng) {
+        let name = Species::NAMES[rng.random_range(0..Species::NAMES.len())];
+        let parameters = Parameters {
+            seed: rng.random(),
+            ..default()
+        };
+
+        plant(
+            commands,
+            meshes,
+            materials,
+            now,
+            Species::from_name(name).unwrap(),
+            parameters,
+            ground.planting_transform(position),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Vec2> {
+        let mut rng = SmallRng::seed_from_u64(0);
+        poisson_disk(FOREST_SIZE, TREE_DISTANCE, &mut rng)
+    }
+
+    #[test]
+    fn points_keep_their_distance() {
+        let points = sample();
+
+        for (n, a) in points.iter().enumerate() {
+            for b in &points[n + 1..] {
+                assert!(a.distance(*b) >= TREE_DISTANCE);
+            }
+        }
+    }
+
+    #[test]
+    fn points_are_inside_the_area() {
+        for point in sample() {
+            assert!(point.abs().cmple(FOREST_SIZE / 2.0).all());
+        }
+    }
+
+    #[test]
+    fn area_is_covered() {
+        //
+        // no place in the area is further than twice the distance from
+        // a point, thus the circles of that radius cover the area
+        //
+        let circle_area = std::f32::consts::PI * (2.0 * TREE_DISTANCE).powi(2);
+        let expected = FOREST_SIZE.x * FOREST_SIZE.y / circle_area;
+
+        assert!(sample().len() as f32 >= expected);
+    }
+}
@@ -0,0 +1,183 @@
+///
+/// Growth curves, giving the length of a branch at a given age.
+///
+/// The sigmoid curves are shifted and rescaled so that
+/// a newly sprouted branch has zero length, while still
+/// approaching 'max_length' as the branch gets older.
+///
+#[derive(Clone, Copy, Debug)]
+pub enum GrowthCurve {
+    ///
+    /// Linear growth of young branches, turning into logarithmic
+    /// growth at the age where the logarithm's slope drops below
+    /// the linear one. The length grows without bounds.
+    ///
+    LinearLog { scale: f32 },
+    ///
+    /// Symmetric S-shaped growth, fastest at 'midpoint' age.
+    ///
+    Logistic {
+        max_length: f32,
+        rate: f32,
+        midpoint: f32,
+    },
+    ///
+    /// Asymmetric S-shaped growth, fast early on and then
+    /// slowly approaching the maximum length.
+    ///
+    Gompertz {
+        max_length: f32,
+        displacement: f32,
+        rate: f32,
+    },
+    ///
+    /// Generalized logistic growth, where 'shape' controls
+    /// near which asymptote the fastest growth occurs.
+    /// A shape of 1.0 gives the logistic curve.
+    ///
+    Richards {
+        max_length: f32,
+        rate: f32,
+        midpoint: f32,
+        shape: f32,
+    },
+}
+
+///
+/// Rescale sigmoid 'curve', running from 0.0 to 1.0, to start at
+/// zero length at age 0.0 and approach 'max_length'.
+///
+fn from_birth(curve: impl Fn(f32) -> f32, max_length: f32, age: f32) -> f32 {
+    let at_birth = curve(0.0);
+
+    max_length * (curve(age) - at_birth) / (1.0 - at_birth)
+}
+
+impl GrowthCurve {
+    pub fn length(&self, age: f32) -> f32 {
+        let age = age.max(0.0);
+
+        match *self {
+            GrowthCurve::LinearLog { scale } => {
+                //
+                // the tangent of ln(age) at age e passes through
+                // origin, making the curve smooth at the switch
+                //
+                let length = if age <= std::f32::consts::E {
+                    age / std::f32::consts::E
+                } else {
+                    age.ln()
+                };
+
+                length * scale
+            }
+            GrowthCurve::Logistic {
+                max_length,
+                rate,
+                midpoint,
+            } => from_birth(
+                |age| 1.0 / (1.0 + (-rate * (age - midpoint)).exp()),
+                max_length,
+                age,
+            ),
+            GrowthCurve::Gompertz {
+                max_length,
+                displacement,
+                rate,
+            } => from_birth(
+                |age| (-displacement * (-rate * age).exp()).exp(),
+                max_length,
+                age,
+            ),
+            GrowthCurve::Richards {
+                max_length,
+                rate,
+                midpoint,
+                shape,
+            } => from_birth(
+                |age| (1.0 + shape * (-rate * (age - midpoint)).exp()).powf(-1.0 / shape),
+                max_length,
+                age,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_AGE: f32 = 600.0;
+    const AGE_STEP: f32 = 0.01;
+
+    fn curves() -> Vec<GrowthCurve> {
+        vec![
+            GrowthCurve::LinearLog { scale: 1.5 },
+            GrowthCurve::Logistic {
+                max_length: 8.0,
+                rate: 0.08,
+                midpoint: 30.0,
+            },
+            GrowthCurve::Gompertz {
+                max_length: 9.0,
+                displacement: 4.0,
+                rate: 0.04,
+            },
+            GrowthCurve::Richards {
+                max_length: 4.0,
+                rate: 0.1,
+                midpoint: 15.0,
+                shape: 0.5,
+            },
+        ]
+    }
+
+    fn ages() -> impl Iterator<Item = f32> {
+        (0..(MAX_AGE / AGE_STEP) as u32).map(|n| n as f32 * AGE_STEP)
+    }
+
+    #[test]
+    fn starts_at_zero() {
+        for curve in curves() {
+            assert!(curve.length(0.0).abs() < 1e-4, "{curve:?}");
+        }
+    }
+
+    #[test]
+    fn is_continuous() {
+        for curve in curves() {
+            for age in ages() {
+                let jump = (curve.length(age + AGE_STEP) - curve.length(age)).abs();
+                assert!(jump < 0.01, "{curve:?} jumps {jump} at age {age}");
+            }
+        }
+    }
+
+    #[test]
+    fn is_monotonic() {
+        for curve in curves() {
+            for age in ages() {
+                assert!(
+                    curve.length(age + AGE_STEP) >= curve.length(age),
+                    "{curve:?} shrinks at age {age}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sigmoids_approach_max_length() {
+        for curve in curves() {
+            let max_length = match curve {
+                GrowthCurve::LinearLog { .. } => continue,
+                GrowthCurve::Logistic { max_length, .. }
+                | GrowthCurve::Gompertz { max_length, .. }
+                | GrowthCurve::Richards { max_length, .. } => max_length,
+            };
+
+            let length = curve.length(MAX_AGE);
+            assert!(length <= max_length + 1e-4, "{curve:?}");
+            assert!(length > max_length * 0.99, "{curve:?}");
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+
+use super::branch::Branch;
+use crate::season::YEAR_LENGTH;
+use crate::timeline::Timeline;
+use crate::tools::BranchClicked;
+
+/// Glow of the selected branch.
+const HIGHLIGHT_COLOR: LinearRgba = LinearRgba::rgb(0.8, 0.5, 0.0);
+
+/// Branch being inspected.
+#[derive(Component)]
+pub struct Selected;
+
+/// On-screen panel listing the selected branch's properties.
+#[derive(Component)]
+pub struct InspectorPanel;
+
+pub fn setup(mut commands: Commands) {
+    commands.spawn((
+        InspectorPanel,
+        Text::default(),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Visibility::Hidden,
+    ));
+}
+
+fn highlight(
+    materials: &mut Assets<StandardMaterial>,
+    material: &MeshMaterial3d<StandardMaterial>,
+    on: bool,
+) {
+    if let Some(material) = materials.get_mut(&material.0) {
+        material.emissive = if on {
+            HIGHLIGHT_COLOR
+        } else {
+            LinearRgba::BLACK
+        };
+    }
+}
+
+///
+/// Select the clicked branch, clicking the
+/// selected branch again deselects it.
+///
+pub fn select(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut clicks: EventReader<BranchClicked>,
+    branches: Query<&MeshMaterial3d<StandardMaterial>, With<Branch>>,
+    selected: Query<(Entity, &MeshMaterial3d<StandardMaterial>), With<Selected>>,
+) {
+    let Some(click) = clicks.read().last() else {
+        return;
+    };
+
+    if let Ok((entity_id, material)) = selected.single() {
+        highlight(&mut materials, material, false);
+        commands.entity(entity_id).remove::<Selected>();
+
+        if entity_id == click.branch {
+            return;
+        }
+    }
+
+    if let Ok(material) = branches.get(click.branch) {
+        highlight(&mut materials, material, true);
+        commands.entity(click.branch).insert(Selected);
+    }
+}
+
+///
+/// Fill the inspector panel with the selected branch's
+/// properties, and hide it when nothing is selected.
+///
+pub fn show(
+    timeline: Res<Timeline>,
+    selected: Query<(&Branch, Option<&Children>), With<Selected>>,
+    branches: Query<(), With<Branch>>,
+    mut panel: Query<(&mut Text, &mut Visibility), With<InspectorPanel>>,
+) {
+    let Ok((mut text, mut visibility)) = panel.single_mut() else {
+        return;
+    };
+
+    let Ok((branch, children)) = selected.single() else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let now = timeline.now();
+    let age = branch.age(now);
+    let angle = branch.angle();
+    let child_branches = children.map_or(0, |children| {
+        children
+            .iter()
+            .filter(|child| branches.contains(*child))
+            .count()
+    });
+    let status = match branch.time_dead(now) {
+        None => "alive".to_string(),
+        Some(time_dead) => format!("dead for {time_dead:.0} s"),
+    };
+
+    text.0 = format!(
+        "order: {}\n\
+         age: {:.1} s ({:.1} years)\n\
+         growth rate: {:.2}\n\
+         length: {:.2}\n\
+         thickness: {:.3}\n\
+         leaf nodes: {}\n\
+         azimuth: {:.0}°\n\
+         inclination: {:.0}°\n\
+         child branches: {}\n\
+         {}",
+        branch.order(),
+        age,
+        age / YEAR_LENGTH,
+        branch.growth_rate(),
+        branch.length(now),
+        branch.thickness(now),
+        branch.leaf_nodes(),
+        angle.azimuth.to_degrees(),
+        angle.inclination.to_degrees(),
+        child_branches,
+        status,
+    );
+    *visibility = Visibility::Visible;
+}
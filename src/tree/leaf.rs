@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+use super::Tree;
+use super::falling::Falling;
+use super::species::Species;
+use crate::assets::LoadedAssets;
+use crate::season::Season;
+use crate::timeline::Timeline;
+use crate::wind::Wind;
+
+/// Number of discoloration steps a leaf goes through as it ages.
+const LEAF_TINTS: usize = 8;
+
+/// Number of color steps of the autumn palette.
+const AUTUMN_TINTS: usize = 8;
+
+/// How much leaves twist in the wind, in radians per unit of wind speed.
+const FLUTTER_FACTOR: f32 = 0.08;
+
+/// Maximum twist of a leaf in the wind, in radians.
+const MAX_FLUTTER: f32 = 0.6;
+
+/// Leaf flutter frequency, in Hz.
+const FLUTTER_FREQUENCY: f32 = 3.0;
+
+/// How far into autumn the first leaves of deciduous trees drop.
+const EARLIEST_AUTUMN_DROP: f32 = 0.4;
+
+/// Size of a leaf bud, relative to the fully grown leaf.
+const BUD_SIZE: f32 = 0.1;
+
+#[derive(Component, Debug)]
+pub struct Leaf {
+    birth_time: f32,
+    tree: Entity,
+    /// node of the branch the leaf grows at
+    node: u32,
+    /// 0.0 to 1.0, hardier leaves stay longer on the tree in autumn
+    hardiness: f32,
+    /// rotation, relative to the branch, when there is no wind
+    rest_rotation: Quat,
+    tint: usize,
+}
+
+impl Leaf {
+    pub fn new(now: f32, tree: Entity, node: u32, hardiness: f32, rest_rotation: Quat) -> Self {
+        Leaf {
+            birth_time: now,
+            tree,
+            node,
+            hardiness,
+            rest_rotation,
+            tint: 0,
+        }
+    }
+
+    pub fn birth_time(&self) -> f32 {
+        self.birth_time
+    }
+
+    pub fn tree(&self) -> Entity {
+        self.tree
+    }
+
+    pub fn node(&self) -> u32 {
+        self.node
+    }
+
+    fn age(&self, now: f32) -> f32 {
+        now - self.birth_time
+    }
+
+    ///
+    /// Size of the leaf, relative to the fully grown leaf.
+    ///
+    pub fn size(&self, species: &Species, now: f32) -> f32 {
+        let unfurled = (self.age(now) / species.leaf_unfurl_time).clamp(0.0, 1.0);
+
+        // ease in and out of unfurling
+        let unfurled = unfurled * unfurled * (3.0 - 2.0 * unfurled);
+
+        BUD_SIZE + (1.0 - BUD_SIZE) * unfurled
+    }
+
+    ///
+    /// How withered the leaf is, from 0.0 for a fresh
+    /// leaf to 1.0 for a leaf about to drop.
+    ///
+    pub fn withering(&self, species: &Species, now: f32) -> f32 {
+        let mature_age = self.age(now) - species.leaf_unfurl_time;
+        let withering = mature_age / (species.leaf_lifespan - species.leaf_unfurl_time);
+
+        // leaves stay green most of their life,
+        // and discolor quickly towards the end
+        withering.clamp(0.0, 1.0).powi(3)
+    }
+
+    ///
+    /// How far the leaf of a deciduous tree has turned through
+    /// the autumn palette, from 0.0 to 1.0 when it's about to drop.
+    ///
+    fn autumn_coloring(&self, autumn_progress: f32) -> f32 {
+        (autumn_progress / self.autumn_drop_point()).clamp(0.0, 1.0)
+    }
+
+    fn autumn_drop_point(&self) -> f32 {
+        EARLIEST_AUTUMN_DROP + (1.0 - EARLIEST_AUTUMN_DROP) * self.hardiness
+    }
+
+    pub fn is_dropping(&self, species: &Species, now: f32) -> bool {
+        if species.deciduous {
+            match Season::at(now) {
+                (Season::Autumn, progress) if progress >= self.autumn_drop_point() => {
+                    return true;
+                }
+                (Season::Winter, _) => return true,
+                _ => { /* nop */ }
+            }
+        }
+
+        self.age(now) >= species.leaf_lifespan
+    }
+
+    ///
+    /// Index of the tree's leaf material to use at the specified time.
+    ///
+    fn tint_at(&self, species: &Species, now: f32) -> usize {
+        match Season::at(now) {
+            (Season::Autumn, progress) if species.deciduous => {
+                let coloring = self.autumn_coloring(progress);
+                LEAF_TINTS + (coloring * (AUTUMN_TINTS - 1) as f32).round() as usize
+            }
+            _ => (self.withering(species, now) * (LEAF_TINTS - 1) as f32).round() as usize,
+        }
+    }
+}
+
+pub fn new_bundle(
+    assets: &LoadedAssets,
+    now: f32,
+    tree: Entity,
+    node: u32,
+    hardiness: f32,
+    transform: Transform,
+) -> impl Bundle {
+    (
+        Leaf::new(now, tree, node, hardiness, transform.rotation),
+        Mesh3d(assets.leaf_mesh.clone()),
+        MeshMaterial3d(assets.leaf_material.clone()),
+        transform.with_scale(Vec3::splat(BUD_SIZE)),
+    )
+}
+
+///
+/// Detach the leaf from its branch, and let it fall to the ground.
+///
+pub fn drop_leaf(commands: &mut Commands, now: f32, entity_id: Entity, leaf: &Leaf) {
+    commands
+        .entity(entity_id)
+        .remove_parent_in_place()
+        .insert(Falling::leaf(now, leaf.hardiness));
+}
+
+///
+/// Sample a color from a gradient running through all colors of the palette.
+///
+fn palette_color(palette: &[Color], factor: f32) -> Color {
+    if palette.len() == 1 {
+        return palette[0];
+    }
+
+    let position = factor * (palette.len() - 1) as f32;
+    let index = (position as usize).min(palette.len() - 2);
+
+    palette[index].mix(&palette[index + 1], position - index as f32)
+}
+
+///
+/// Create the materials used for discolored leaves of
+/// each tree, once the leaf material has been loaded.
+///
+/// The first 'LEAF_TINTS' materials are for aging leaves,
+/// followed by 'AUTUMN_TINTS' materials with colors from
+/// the species' autumn palette.
+///
+pub fn tint_materials(
+    assets: Res<LoadedAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut trees: Query<&mut Tree>,
+) {
+    for mut tree in trees.iter_mut() {
+        if !tree.leaf_materials.is_empty() {
+            continue;
+        }
+
+        let Some(leaf_material) = materials.get(&assets.leaf_material).cloned() else {
+            // not loaded yet
+            return;
+        };
+
+        let aging_palette = [leaf_material.base_color, tree.species.withered_leaf_color];
+        let aging_colors = (0..LEAF_TINTS)
+            .map(|n| palette_color(&aging_palette, n as f32 / (LEAF_TINTS - 1) as f32));
+
+        // autumn coloring starts from the fresh leaf color
+        let autumn_palette: Vec<Color> = std::iter::once(leaf_material.base_color)
+            .chain(tree.species.autumn_palette.iter().copied())
+            .collect();
+        let autumn_colors = (0..AUTUMN_TINTS)
+            .map(|n| palette_color(&autumn_palette, n as f32 / (AUTUMN_TINTS - 1) as f32));
+
+        tree.leaf_materials = aging_colors
+            .chain(autumn_colors)
+            .map(|base_color| {
+                materials.add(StandardMaterial {
+                    base_color,
+                    ..leaf_material.clone()
+                })
+            })
+            .collect();
+    }
+}
+
+///
+/// Unfurl, discolor and drop leaves as they age and seasons change.
+///
+pub fn update(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    trees: Query<&Tree>,
+    mut leaves: Query<
+        (
+            Entity,
+            &mut Leaf,
+            &mut Transform,
+            &mut MeshMaterial3d<StandardMaterial>,
+        ),
+        Without<Falling>,
+    >,
+) {
+    let now = timeline.now();
+
+    for (entity_id, mut leaf, mut transform, mut material) in leaves.iter_mut() {
+        // the tree may have been regrown, leaving this leaf behind
+        let Ok(tree) = trees.get(leaf.tree) else {
+            continue;
+        };
+
+        // leaves don't drop while the timeline is rewound
+        if timeline.is_live() && leaf.is_dropping(&tree.species, now) {
+            drop_leaf(&mut commands, now, entity_id, &leaf);
+            continue;
+        }
+
+        transform.scale = Vec3::splat(leaf.size(&tree.species, now));
+
+        let tint = leaf.tint_at(&tree.species, now);
+        if tint == leaf.tint {
+            continue;
+        }
+
+        if let Some(tinted_material) = tree.leaf_materials.get(tint) {
+            material.0 = tinted_material.clone();
+            leaf.tint = tint;
+        }
+    }
+}
+
+///
+/// Flutter leaves in the wind.
+///
+pub fn flutter(
+    timeline: Res<Timeline>,
+    wind: Res<Wind>,
+    mut leaves: Query<(&Leaf, &mut Transform), Without<Falling>>,
+) {
+    let now = timeline.now();
+    let amplitude = (wind.speed(now) * FLUTTER_FACTOR).min(MAX_FLUTTER);
+
+    for (leaf, mut transform) in leaves.iter_mut() {
+        let phase = leaf.hardiness * TAU;
+        let twist = amplitude * (now * FLUTTER_FREQUENCY * TAU + phase).sin();
+        let lift = amplitude * 0.5 * (now * FLUTTER_FREQUENCY * 1.7 * TAU + phase).sin();
+
+        transform.rotation =
+            leaf.rest_rotation * Quat::from_rotation_z(twist) * Quat::from_rotation_x(lift);
+    }
+}
@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use super::branch::Branch;
+
+/// How quickly light fades with depth into a crown, per unit of depth.
+const LIGHT_EXTINCTION: f32 = 0.35;
+
+///
+/// Fraction of full light reaching 'depth' below the top of a crown.
+///
+/// Follows Beer-Lambert law, treating the crown
+/// as an evenly dense volume of leaves.
+///
+pub fn exposure(depth: f32) -> f32 {
+    (-LIGHT_EXTINCTION * depth.max(0.0)).exp()
+}
+
+///
+/// Extent of a tree's crown, approximated by a vertical
+/// cylinder around the trunk, reaching up to the top.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Crown {
+    /// where the trunk stands, on the ground
+    pub center: Vec2,
+    /// height of the highest branch tip
+    pub top: f32,
+    /// horizontal distance from the trunk to the furthest branch tip
+    pub radius: f32,
+}
+
+impl Crown {
+    fn covers(&self, point: Vec3) -> bool {
+        point.xz().distance(self.center) <= self.radius
+    }
+}
+
+///
+/// Crowns of all trees, from the tips of their branches.
+///
+pub fn crowns<'a>(
+    trees: impl Iterator<Item = (Entity, &'a GlobalTransform)>,
+    branches: impl Iterator<Item = (&'a Branch, &'a GlobalTransform)>,
+    now: f32,
+) -> HashMap<Entity, Crown> {
+    let mut crowns: HashMap<Entity, Crown> = trees
+        .map(|(tree, transform)| {
+            let position = transform.translation();
+            let crown = Crown {
+                center: position.xz(),
+                top: position.y,
+                radius: 0.0,
+            };
+            (tree, crown)
+        })
+        .collect();
+
+    for (branch, transform) in branches {
+        let Some(crown) = crowns.get_mut(&branch.tree()) else {
+            continue;
+        };
+
+        let tip = branch.tip(transform, now);
+        crown.top = crown.top.max(tip.y);
+        crown.radius = crown.radius.max(tip.xz().distance(crown.center));
+    }
+
+    crowns
+}
+
+///
+/// Fraction of full light reaching 'point' on 'tree', shaded
+/// by its own crown, and the crowns of neighbours covering it.
+///
+pub fn exposure_at(crowns: &HashMap<Entity, Crown>, tree: Entity, point: Vec3) -> f32 {
+    let depth = crowns
+        .iter()
+        .filter(|(entity, crown)| **entity == tree || crown.covers(point))
+        .map(|(_, crown)| crown.top - point.y)
+        .fold(0.0, f32::max);
+
+    exposure(depth)
+}
@@ -1,108 +1,487 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, render::primitives::Aabb};
 pub mod branch;
 use branch::Branch;
+pub mod competition;
+pub mod death;
+pub mod export;
+pub mod falling;
+use falling::Falling;
+mod forest;
+pub mod growth;
+pub mod inspector;
+pub mod leaf;
+use leaf::Leaf;
+pub mod pruning;
+pub mod roots;
+use roots::Root;
 
-use rand::SeedableRng;
 use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::f32::consts::{FRAC_PI_2, PI};
 
 mod angles;
-use angles::new_branch_angle;
+mod light;
+use angles::{BranchAngle, new_branch_angle};
 
-const RND_SEED: u64 = 0;
+mod parameters;
+use parameters::Parameters;
+
+mod species;
+use species::Species;
+
+use crate::ground::Ground;
+use crate::obstacles::{self, Bounds, Obstacle};
+use crate::timeline::Timeline;
+
+/// Largest share of new branches a tree gives up growing away from the sun.
+const PHOTOTROPISM: f32 = 0.6;
+
+///
+/// Regrow all trees from scratch, with their current
+/// species and parameters.
+///
+#[derive(Event)]
+pub struct Regrow;
 
 #[derive(Component)]
+#[require(Transform, Visibility)]
 pub struct Tree {
-    branch_angles: Vec<f32>,
+    species: Species,
+    parameters: Parameters,
+    /// angles of the branches growing on each parent, new ones keep away from
+    branch_angles: HashMap<Entity, Vec<(Entity, BranchAngle)>>,
+    /// branches the trunk has grown, not counting shoots where it was pruned
+    trunk_branches: usize,
+    root_angles: Vec<BranchAngle>,
+    /// horizontal direction towards crowding neighbours, up to unit length
+    crowding: Vec2,
+    /// direction towards the sun, scaled by the strength of the sunlight
+    sunlight: Vec3,
+    /// obstacles new branches grow around
+    obstacles: Vec<Bounds>,
+    leaf_materials: Vec<Handle<StandardMaterial>>,
     rng: SmallRng,
 }
 
 impl Tree {
-    pub fn new() -> Self {
+    pub fn new(species: Species, parameters: Parameters) -> Self {
+        let rng = SmallRng::seed_from_u64(parameters.seed);
+
         Tree {
-            branch_angles: vec![],
-            rng: SmallRng::seed_from_u64(RND_SEED),
+            species,
+            parameters,
+            branch_angles: HashMap::new(),
+            trunk_branches: 0,
+            root_angles: vec![],
+            crowding: Vec2::ZERO,
+            sunlight: Vec3::ZERO,
+            obstacles: vec![],
+            leaf_materials: vec![],
+            rng,
+        }
+    }
+
+    pub fn species_mut(&mut self) -> &mut Species {
+        &mut self.species
+    }
+
+    pub fn parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.parameters
+    }
+
+    ///
+    /// Sample the direction of a new branch on 'parent', sprouting at
+    /// 'origin' on a parent rotated by 'parent_rotation', in world
+    /// coordinates. Returns the direction together with its free length.
+    ///
+    pub fn get_new_branch_angle(
+        &mut self,
+        parent: Entity,
+        order: u32,
+        relative_height: f32,
+        origin: Vec3,
+        parent_rotation: Quat,
+    ) -> (BranchAngle, f32) {
+        let inclination = self.species.branch_inclination(order, relative_height);
+
+        // new branches keep away from their siblings
+        let sibling_angles: Vec<BranchAngle> = self
+            .branch_angles
+            .get(&parent)
+            .into_iter()
+            .flatten()
+            .map(|(_, angle)| *angle)
+            .collect();
+
+        loop {
+            let (angle, free_length) = new_branch_angle(
+                &sibling_angles,
+                inclination,
+                self.species.inclination_jitter,
+                &self.parameters,
+                &mut self.rng,
+                |angle| {
+                    let direction = Dir3::new(parent_rotation * angle.direction()).unwrap();
+                    obstacles::clearance(&self.obstacles, origin, direction)
+                },
+            );
+
+            let direction = (parent_rotation * angle.direction())
+                .xz()
+                .normalize_or_zero();
+
+            //
+            // branches sprouting from the trunk are likely to
+            // be rejected when growing towards crowding neighbours
+            //
+            let towards_neighbours = if order == 1 {
+                direction.dot(self.crowding)
+            } else {
+                0.0
+            };
+
+            //
+            // and all branches when growing away from the sun,
+            // the more the lower it is in the sky
+            //
+            let away_from_sun = -direction.dot(self.sunlight.xz()) * PHOTOTROPISM;
+
+            if self.rng.random_range(0.0..1.0) >= towards_neighbours.max(away_from_sun) {
+                return (angle, free_length);
+            }
         }
     }
 
-    pub fn get_new_branch_angle(&mut self) -> f32 {
-        new_branch_angle(&self.branch_angles, &mut self.rng)
+    ///
+    /// Rotations, relative to the branch, of
+    /// the leaves sprouting at the specified node.
+    ///
+    pub fn get_leaf_rotations(&mut self, node: u32) -> Vec<Quat> {
+        let species = &self.species;
+        let rng = &mut self.rng;
+
+        species
+            .phyllotaxis
+            .leaf_azimuths(node)
+            .into_iter()
+            .map(|azimuth| {
+                let azimuth = azimuth + rng.random_range(-1.0..1.0) * species.leaf_jitter;
+                let inclination =
+                    species.leaf_inclination + rng.random_range(-1.0..1.0) * species.leaf_jitter;
+
+                // leaf mesh points along -Z, tilt it up towards the branch axis
+                Quat::from_rotation_y(azimuth) * Quat::from_rotation_x(FRAC_PI_2 - inclination)
+            })
+            .collect()
+    }
+
+    ///
+    /// Forget about 'branch', and the shoots growing on it, cut off
+    /// from 'parent', letting new branches grow where it used to be.
+    ///
+    pub fn forget_branch(&mut self, parent: Entity, branch: Entity) {
+        if let Some(siblings) = self.branch_angles.get_mut(&parent) {
+            siblings.retain(|(sibling, _)| *sibling != branch);
+        }
+
+        let mut forgotten = vec![branch];
+        while let Some(branch) = forgotten.pop() {
+            if let Some(shoots) = self.branch_angles.remove(&branch) {
+                forgotten.extend(shoots.into_iter().map(|(shoot, _)| shoot));
+            }
+        }
     }
 }
 
-fn maybe_add_branch(
+///
+/// Sprout a new branch on 'parent', at 'height' along it.
+///
+fn sprout(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     now: f32,
     tree: &mut Tree,
-    trunk: &Branch,
+    (parent_entity, parent, parent_transform): (Entity, &Branch, &GlobalTransform),
+    height: f32,
 ) {
-    let trunk_length = trunk.length(now);
-    let expected_children = (trunk_length / 0.69) as usize;
+    let order = parent.order() + 1;
+    // a parent just born, or cut back to nothing, has no length yet
+    let relative_height = (height / parent.length(now).max(f32::EPSILON)).min(1.0);
+    let (new_branch_angle, free_length) = tree.get_new_branch_angle(
+        parent_entity,
+        order,
+        relative_height,
+        parent_transform.transform_point(Vec3::Y * height),
+        parent_transform.rotation(),
+    );
 
-    while expected_children > tree.branch_angles.len() {
-        let new_branch_angle = tree.get_new_branch_angle();
+    let branch = branch::spawn_new(
+        commands,
+        meshes,
+        materials,
+        now,
+        Branch::new(
+            now,
+            tree.species.growth_curve,
+            tree.parameters.branch_growth_rate,
+            order,
+            new_branch_angle,
+            parent.tree(),
+        )
+        .with_reach(free_length),
+        height,
+        tree.parameters.segments,
+    );
 
-        tree.branch_angles.push(new_branch_angle);
+    commands.entity(parent_entity).add_child(branch);
 
-        branch::spawn_new(
+    tree.branch_angles
+        .entry(parent_entity)
+        .or_default()
+        .push((branch, new_branch_angle));
+}
+
+fn maybe_add_branch(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    now: f32,
+    tree: &mut Tree,
+    (trunk_entity, trunk, trunk_transform): (Entity, &Branch, &GlobalTransform),
+) {
+    let trunk_length = trunk.length(now);
+    let expected_children = (trunk_length / tree.parameters.branch_spacing) as usize;
+
+    while expected_children > tree.trunk_branches {
+        sprout(
             commands,
             meshes,
             materials,
             now,
-            0.38,
-            trunk_length * 0.58,
-            new_branch_angle,
-            0.5,
+            tree,
+            (trunk_entity, trunk, trunk_transform),
+            trunk_length * tree.parameters.branch_height,
         );
+        tree.trunk_branches += 1;
     }
 }
 
 pub fn update(
     mut commands: Commands,
-    time: Res<Time>,
+    timeline: Res<Timeline>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut trees: Query<(&mut Tree, &Children)>,
-    branches: Query<&Branch>,
+    branches: Query<(&Branch, &GlobalTransform), Without<Root>>,
 ) {
-    let now = time.elapsed_secs();
+    let now = timeline.now();
 
     for (mut tree, children) in trees.iter_mut() {
         for child in children.iter() {
-            let trunk = branches.get(child).unwrap();
+            let Ok((trunk, trunk_transform)) = branches.get(child) else {
+                // the taproot
+                continue;
+            };
             maybe_add_branch(
                 &mut commands,
                 &mut meshes,
                 &mut materials,
                 now,
                 &mut tree,
-                trunk,
+                (child, trunk, trunk_transform),
             );
         }
     }
 }
 
+///
+/// Plant a new tree, placed by 'transform', with a
+/// trunk and a taproot to grow from.
+///
+fn plant(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    now: f32,
+    species: Species,
+    parameters: Parameters,
+    transform: Transform,
+) {
+    let growth_curve = species.growth_curve;
+    let segments = parameters.segments;
+    let root_growth_rate = parameters.root_growth_rate;
+    let tree = commands
+        .spawn((Tree::new(species, parameters), transform))
+        .id();
+
+    let trunk = branch::spawn_new(
+        commands,
+        meshes,
+        materials,
+        now,
+        Branch::new(now, growth_curve, 1.0, 0, BranchAngle::default(), tree),
+        0.0,
+        segments,
+    );
+
+    // growing straight down
+    let taproot_angle = BranchAngle {
+        azimuth: 0.0,
+        inclination: PI,
+    };
+    let taproot = branch::spawn_new(
+        commands,
+        meshes,
+        materials,
+        now,
+        Branch::new(now, growth_curve, root_growth_rate, 0, taproot_angle, tree),
+        0.0,
+        segments,
+    );
+    commands.entity(taproot).insert(Root);
+
+    commands.entity(tree).add_children(&[trunk, taproot]);
+}
+
+pub fn regrow(
+    mut commands: Commands,
+    mut timeline: ResMut<Timeline>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut requests: EventReader<Regrow>,
+    trees: Query<(Entity, &Tree, &Transform)>,
+) {
+    if requests.read().count() == 0 {
+        return;
+    }
+
+    // regrow from the live time
+    timeline.go_live();
+
+    let now = timeline.now();
+
+    for (entity_id, tree, transform) in trees.iter() {
+        commands.entity(entity_id).despawn();
+        plant(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            now,
+            tree.species.clone(),
+            tree.parameters.clone(),
+            *transform,
+        );
+    }
+}
+
+///
+/// Let trees know of the obstacles their new branches grow around.
+///
+pub fn sense_obstacles(
+    mut trees: Query<&mut Tree>,
+    obstacles: Query<(&GlobalTransform, &Aabb), With<Obstacle>>,
+) {
+    let obstacles: Vec<Bounds> = obstacles
+        .iter()
+        .map(|(transform, aabb)| Bounds::new(transform, aabb))
+        .collect();
+
+    for mut tree in trees.iter_mut() {
+        tree.obstacles.clone_from(&obstacles);
+    }
+}
+
+///
+/// Hide branches and leaves born after the time shown, and
+/// ones lying on the ground that dropped after the time shown,
+/// when the timeline is rewound.
+///
+pub fn hide_unborn(
+    timeline: Res<Timeline>,
+    mut branches: Query<(&Branch, Option<&Falling>, &mut Visibility)>,
+    mut leaves: Query<(&Leaf, Option<&Falling>, &mut Visibility), Without<Branch>>,
+) {
+    let now = timeline.now();
+
+    let visibility = |birth_time: f32, falling: Option<&Falling>| {
+        let dropped_later = falling.is_some_and(|falling| falling.drop_time() > now);
+
+        if birth_time > now || dropped_later {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        }
+    };
+
+    for (branch, falling, mut branch_visibility) in branches.iter_mut() {
+        branch_visibility.set_if_neq(visibility(branch.birth_time(), falling));
+    }
+
+    for (leaf, falling, mut leaf_visibility) in leaves.iter_mut() {
+        leaf_visibility.set_if_neq(visibility(leaf.birth_time(), falling));
+    }
+}
+
 pub fn setup(
     mut commands: Commands,
-    time: Res<Time>,
+    timeline: Res<Timeline>,
+    ground: Res<Ground>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let now = time.elapsed_secs();
+    let now = timeline.now();
 
-    let trunk = branch::spawn_new(
+    //
+    // species can be selected with the first command line
+    // argument, or a forest of mixed species planted
+    //
+    let species = match std::env::args().nth(1).as_deref() {
+        Some("forest") => {
+            forest::plant_forest(&mut commands, &mut meshes, &mut materials, now, &ground);
+            return;
+        }
+        None => Species::maple(),
+        Some(name) => Species::from_name(name).unwrap_or_else(|| {
+            warn!("unknown species '{name}', growing a maple");
+            Species::maple()
+        }),
+    };
+
+    plant(
         &mut commands,
         &mut meshes,
         &mut materials,
         now,
-        1.0,
-        0.0,
-        0.0,
-        0.0,
+        species,
+        Parameters::default(),
+        ground.planting_transform(Vec2::ZERO),
     );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let tree = commands.spawn((Tree::new(),)).id();
+    #[test]
+    fn forgets_cut_branches_with_their_shoots() {
+        let mut tree = Tree::new(Species::oak(), Parameters::default());
+        let [trunk, cut, shoot, twig, kept] = [1, 2, 3, 4, 5].map(Entity::from_raw);
 
-    commands.entity(tree).add_child(trunk);
+        let angle = BranchAngle::default();
+        tree.branch_angles
+            .insert(trunk, vec![(cut, angle), (kept, angle)]);
+        tree.branch_angles.insert(cut, vec![(shoot, angle)]);
+        tree.branch_angles.insert(shoot, vec![(twig, angle)]);
+
+        tree.forget_branch(trunk, cut);
+
+        assert_eq!(tree.branch_angles.len(), 1);
+        let siblings: Vec<Entity> = tree.branch_angles[&trunk]
+            .iter()
+            .map(|(sibling, _)| *sibling)
+            .collect();
+        assert_eq!(siblings, [kept]);
+    }
 }
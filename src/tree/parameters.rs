@@ -0,0 +1,52 @@
+///
+/// Parameters of the growth model, shared by all species.
+///
+/// Branching parameters take effect on the running tree as new
+/// branches sprout, the rest take a regrowth to take full effect.
+///
+#[derive(Clone, Debug)]
+pub struct Parameters {
+    /// length of the trunk per branch sprouting from it
+    pub branch_spacing: f32,
+    /// where on the trunk new branches sprout, relative to its length
+    pub branch_height: f32,
+    /// growth rate of branches, relative to the trunk
+    pub branch_growth_rate: f32,
+    /// angular range, in radians, within which new branches avoid existing ones
+    pub range_width: f32,
+    /// how close, in radians, new branches may get to existing ones
+    pub bell_width: f32,
+    /// distance between leaf nodes along a branch
+    pub leaf_spacing: f32,
+    /// growth rate of the taproot, relative to the trunk
+    pub root_growth_rate: f32,
+    /// length of the taproot per lateral root sprouting from it
+    pub root_spacing: f32,
+    /// angle, in radians, between lateral roots and the taproot
+    pub root_inclination: f32,
+    /// root mass the tree keeps, per unit of crown mass
+    pub root_ratio: f32,
+    /// number of sides of a branch mesh
+    pub segments: usize,
+    /// seed for the tree's random number generator
+    pub seed: u64,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Parameters {
+            branch_spacing: 0.69,
+            branch_height: 0.58,
+            branch_growth_rate: 0.38,
+            range_width: 2.0,
+            bell_width: 0.6,
+            leaf_spacing: 0.24,
+            root_growth_rate: 0.4,
+            root_spacing: 0.3,
+            root_inclination: 1.3,
+            root_ratio: 0.3,
+            segments: 5,
+            seed: 0,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+
+use super::branch::Branch;
+use super::{Tree, sprout};
+use crate::timeline::Timeline;
+use crate::tools::BranchClicked;
+
+///
+/// Cut off clicked branches, together with everything growing on them.
+///
+/// New shoots sprout on the parent, where the branch was cut off,
+/// as many as the species of the tree grows.
+///
+pub fn cut(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut clicks: EventReader<BranchClicked>,
+    mut trees: Query<&mut Tree>,
+    branches: Query<(&Branch, &Transform, &GlobalTransform, &ChildOf)>,
+) {
+    let now = timeline.now();
+
+    for click in clicks.read() {
+        let Ok((branch, transform, _, child_of)) = branches.get(click.branch) else {
+            // falling branches can't be pruned
+            continue;
+        };
+
+        if branch.order() == 0 {
+            info!("the trunk can't be pruned");
+            continue;
+        }
+
+        commands.entity(click.branch).despawn();
+
+        let parent_entity = child_of.parent();
+        let Ok(mut tree) = trees.get_mut(branch.tree()) else {
+            continue;
+        };
+        tree.forget_branch(parent_entity, click.branch);
+
+        let Ok((parent, _, parent_transform, _)) = branches.get(parent_entity) else {
+            continue;
+        };
+
+        if parent.is_dead(now) {
+            continue;
+        }
+
+        for _ in 0..tree.species.resprout_shoots {
+            sprout(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                now,
+                &mut tree,
+                (parent_entity, parent, parent_transform),
+                transform.translation.y,
+            );
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::collections::HashMap;
+
+use super::Tree;
+use super::angles::new_branch_angle;
+use super::branch::{self, Branch};
+use super::falling::Falling;
+use crate::timeline::Timeline;
+
+/// Vigor of roots far ahead of the crown.
+const MIN_ROOT_VIGOR: f32 = 0.1;
+
+/// Growth rate of lateral roots, relative to the taproot.
+const LATERAL_GROWTH_RATE: f32 = 0.6;
+
+///
+/// Branch growing underground.
+///
+/// The taproot grows straight down from the base of the trunk,
+/// with lateral roots sprouting along it.
+///
+#[derive(Component)]
+pub struct Root;
+
+/// Filter for branches of the crown, attached to the tree above ground.
+pub type InCrown = (Without<Falling>, Without<Root>);
+
+///
+/// Mass of a branch, up to a constant factor.
+///
+fn mass(branch: &Branch, now: f32) -> f32 {
+    branch.length(now) * branch.thickness(now).powi(2)
+}
+
+///
+/// Grow roots in balance with the crown.
+///
+/// Roots grow at full vigor while the root mass lags behind the
+/// crown's, and slow down when ahead. Lateral roots sprout along
+/// the taproot as it reaches deeper.
+///
+pub fn grow(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut trees: Query<&mut Tree>,
+    mut branches: Query<(Entity, &mut Branch, Has<Root>), Without<Falling>>,
+) {
+    let now = timeline.now();
+
+    //
+    // crown and root mass of each tree
+    //
+    let mut masses: HashMap<Entity, (f32, f32)> = HashMap::new();
+    for (_, branch, is_root) in branches.iter() {
+        let (crown_mass, root_mass) = masses.entry(branch.tree()).or_default();
+
+        if is_root {
+            *root_mass += mass(branch, now);
+        } else {
+            *crown_mass += mass(branch, now);
+        }
+    }
+
+    for (entity_id, mut branch, is_root) in branches.iter_mut() {
+        if !is_root {
+            continue;
+        }
+
+        let Ok(mut tree) = trees.get_mut(branch.tree()) else {
+            continue;
+        };
+        let tree = &mut *tree;
+
+        let (crown_mass, root_mass) = masses[&branch.tree()];
+        let balance = tree.parameters.root_ratio * crown_mass / root_mass.max(f32::EPSILON);
+        branch.set_vigor(now, balance.clamp(MIN_ROOT_VIGOR, 1.0));
+
+        if branch.order() != 0 {
+            continue;
+        }
+
+        let taproot_length = branch.length(now);
+        let expected_laterals = (taproot_length / tree.parameters.root_spacing) as usize;
+
+        while expected_laterals > tree.root_angles.len() {
+            // obstacles stand above ground, out of the way of roots
+            let (angle, _) = new_branch_angle(
+                &tree.root_angles,
+                tree.parameters.root_inclination,
+                tree.species.inclination_jitter,
+                &tree.parameters,
+                &mut tree.rng,
+                |_| f32::INFINITY,
+            );
+            tree.root_angles.push(angle);
+
+            let height = taproot_length * tree.rng.random_range(0.2..0.9);
+            let lateral = branch::spawn_new(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                now,
+                Branch::new(
+                    now,
+                    tree.species.growth_curve,
+                    tree.parameters.root_growth_rate * LATERAL_GROWTH_RATE,
+                    1,
+                    angle,
+                    branch.tree(),
+                ),
+                height,
+                tree.parameters.segments,
+            );
+
+            commands.entity(lateral).insert(Root);
+            commands.entity(entity_id).add_child(lateral);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use super::growth::GrowthCurve;
+
+///
+/// Arrangement of leaves along a branch.
+///
+/// Leaves sprout at nodes, spaced at regular
+/// intervals along the branch.
+///
+#[derive(Clone, Copy, Debug)]
+pub enum Phyllotaxis {
+    /// a pair of leaves, on opposite sides, at each node
+    Opposite,
+    /// opposite pairs, each pair rotated 90° from the previous one
+    Decussate,
+    /// a single leaf at each node, rotated by 'divergence' radians from the previous one
+    Alternate { divergence: f32 },
+    /// 'count' leaves evenly spread around each node
+    Whorled { count: u32 },
+}
+
+impl Phyllotaxis {
+    ///
+    /// Azimuths of the leaves sprouting at the specified node.
+    ///
+    pub fn leaf_azimuths(&self, node: u32) -> Vec<f32> {
+        let node = node as f32;
+
+        match *self {
+            Phyllotaxis::Opposite => vec![0.0, PI],
+            Phyllotaxis::Decussate => {
+                let azimuth = node * FRAC_PI_2;
+                vec![azimuth, azimuth + PI]
+            }
+            Phyllotaxis::Alternate { divergence } => vec![node * divergence],
+            Phyllotaxis::Whorled { count } => {
+                let step = TAU / count as f32;
+                // place leaves of a whorl in the gaps of the previous one
+                let offset = node * step / 2.0;
+
+                (0..count).map(|n| offset + n as f32 * step).collect()
+            }
+        }
+    }
+}
+
+///
+/// Parameters giving trees of a species their characteristic shape.
+///
+/// Inclinations are angles, in radians, between a branch
+/// and the axis of the parent it sprouts from.
+///
+#[derive(Clone, Debug)]
+pub struct Species {
+    /// length, by age, of branches with unit growth rate
+    pub growth_curve: GrowthCurve,
+    /// least fraction of full light a branch survives on
+    pub shade_tolerance: f32,
+    /// number of new shoots sprouting where a branch has been cut off
+    pub resprout_shoots: u32,
+    /// inclination of first order branches sprouting at the base of their parent
+    pub base_inclination: f32,
+    /// inclination added for each branch order above the first
+    pub order_inclination: f32,
+    /// how much more upright branches sprouting at the tip of their parent are
+    pub apical_inclination: f32,
+    /// maximum random deviation from the computed inclination
+    pub inclination_jitter: f32,
+    /// arrangement of leaves along the branches
+    pub phyllotaxis: Phyllotaxis,
+    /// angle between a leaf and the axis of its branch
+    pub leaf_inclination: f32,
+    /// maximum random deviation of a leaf's azimuth and inclination
+    pub leaf_jitter: f32,
+    /// seconds it takes a leaf to unfurl from a bud to full size
+    pub leaf_unfurl_time: f32,
+    /// seconds from budding until a leaf drops
+    pub leaf_lifespan: f32,
+    /// color of a leaf about to drop
+    pub withered_leaf_color: Color,
+    /// deciduous trees drop all their leaves in autumn, evergreens keep them
+    pub deciduous: bool,
+    /// colors leaves of deciduous trees turn through in autumn
+    pub autumn_palette: Vec<Color>,
+}
+
+impl Species {
+    pub fn maple() -> Self {
+        Species {
+            growth_curve: GrowthCurve::LinearLog { scale: 1.5 },
+            base_inclination: 0.9,
+            order_inclination: 0.2,
+            apical_inclination: 0.6,
+            inclination_jitter: 0.15,
+            shade_tolerance: 0.3,
+            resprout_shoots: 2,
+            phyllotaxis: Phyllotaxis::Opposite,
+            leaf_inclination: 1.2,
+            leaf_jitter: 0.2,
+            leaf_unfurl_time: 5.0,
+            leaf_lifespan: 100.0,
+            withered_leaf_color: Color::srgb(0.60, 0.42, 0.12),
+            deciduous: true,
+            autumn_palette: vec![
+                Color::srgb(0.95, 0.80, 0.10),
+                Color::srgb(0.95, 0.45, 0.05),
+                Color::srgb(0.75, 0.10, 0.05),
+            ],
+        }
+    }
+
+    pub fn chestnut() -> Self {
+        Species {
+            growth_curve: GrowthCurve::Logistic {
+                max_length: 8.0,
+                rate: 0.08,
+                midpoint: 30.0,
+            },
+            base_inclination: 1.1,
+            order_inclination: 0.2,
+            apical_inclination: 0.7,
+            inclination_jitter: 0.2,
+            shade_tolerance: 0.35,
+            resprout_shoots: 2,
+            phyllotaxis: Phyllotaxis::Decussate,
+            leaf_inclination: 1.0,
+            leaf_jitter: 0.15,
+            leaf_unfurl_time: 6.0,
+            leaf_lifespan: 100.0,
+            withered_leaf_color: Color::srgb(0.50, 0.33, 0.15),
+            deciduous: true,
+            autumn_palette: vec![
+                Color::srgb(0.85, 0.75, 0.15),
+                Color::srgb(0.70, 0.45, 0.10),
+                Color::srgb(0.45, 0.28, 0.10),
+            ],
+        }
+    }
+
+    pub fn oak() -> Self {
+        Species {
+            growth_curve: GrowthCurve::Gompertz {
+                max_length: 9.0,
+                displacement: 4.0,
+                rate: 0.04,
+            },
+            base_inclination: 1.3,
+            order_inclination: 0.15,
+            apical_inclination: 0.5,
+            inclination_jitter: 0.3,
+            shade_tolerance: 0.45,
+            resprout_shoots: 1,
+            // the golden angle, giving a spiral arrangement
+            phyllotaxis: Phyllotaxis::Alternate { divergence: 2.4 },
+            leaf_inclination: 1.3,
+            leaf_jitter: 0.3,
+            leaf_unfurl_time: 7.0,
+            leaf_lifespan: 110.0,
+            withered_leaf_color: Color::srgb(0.45, 0.35, 0.18),
+            deciduous: true,
+            autumn_palette: vec![
+                Color::srgb(0.70, 0.60, 0.15),
+                Color::srgb(0.65, 0.35, 0.10),
+                Color::srgb(0.50, 0.25, 0.10),
+            ],
+        }
+    }
+
+    pub fn oleander() -> Self {
+        Species {
+            growth_curve: GrowthCurve::Richards {
+                max_length: 4.0,
+                rate: 0.1,
+                midpoint: 15.0,
+                shape: 0.5,
+            },
+            base_inclination: 0.6,
+            order_inclination: 0.1,
+            apical_inclination: 0.4,
+            inclination_jitter: 0.1,
+            shade_tolerance: 0.25,
+            resprout_shoots: 3,
+            phyllotaxis: Phyllotaxis::Whorled { count: 3 },
+            leaf_inclination: 0.8,
+            leaf_jitter: 0.1,
+            leaf_unfurl_time: 4.0,
+            leaf_lifespan: 150.0,
+            withered_leaf_color: Color::srgb(0.55, 0.55, 0.25),
+            deciduous: false,
+            autumn_palette: vec![],
+        }
+    }
+
+    /// Names of all species, as accepted by 'from_name'.
+    pub const NAMES: [&str; 4] = ["maple", "chestnut", "oak", "oleander"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "maple" => Some(Species::maple()),
+            "chestnut" => Some(Species::chestnut()),
+            "oak" => Some(Species::oak()),
+            "oleander" => Some(Species::oleander()),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Mean inclination of a new branch of 'order', sprouting at
+    /// 'relative_height' on its parent, where 0.0 is the parent's
+    /// base and 1.0 its tip.
+    ///
+    pub fn branch_inclination(&self, order: u32, relative_height: f32) -> f32 {
+        let order_offset = order.saturating_sub(1) as f32 * self.order_inclination;
+
+        self.base_inclination + order_offset - self.apical_inclination * relative_height
+    }
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+use crate::bindings::{Action, Controls};
+
+/// Wind strength change per key press.
+const STRENGTH_STEP: f32 = 0.5;
+
+/// Gustiness change per key press.
+const GUSTINESS_STEP: f32 = 0.1;
+
+pub struct WindPlugin;
+
+impl Plugin for WindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wind>().add_systems(Update, change_wind);
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct Wind {
+    /// horizontal direction the wind blows in
+    pub direction: Vec3,
+    /// mean wind speed
+    pub strength: f32,
+    /// 0.0 for a steady wind, up to 1.0 for
+    /// gusts stilling and doubling the wind
+    pub gustiness: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Wind {
+            direction: Vec3::new(0.94, 0.0, 0.34),
+            strength: 1.0,
+            gustiness: 0.4,
+        }
+    }
+}
+
+impl Wind {
+    ///
+    /// Wind speed at the specified time, varying with the gusts.
+    ///
+    pub fn speed(&self, now: f32) -> f32 {
+        //
+        // a few overlapping waves of different
+        // frequencies, giving irregular gusts
+        //
+        let gusts = ((now * 0.11 * TAU).sin()
+            + (now * 0.29 * TAU + 1.0).sin() * 0.5
+            + (now * 0.73 * TAU + 2.0).sin() * 0.25)
+            / 1.75;
+
+        self.strength * (1.0 + self.gustiness * gusts)
+    }
+
+    pub fn velocity(&self, now: f32) -> Vec3 {
+        self.direction * self.speed(now)
+    }
+}
+
+fn change_wind(controls: Controls, mut wind: ResMut<Wind>) {
+    let mut strength = wind.strength;
+    let mut gustiness = wind.gustiness;
+
+    if controls.just_pressed(Action::WindStronger) {
+        strength += STRENGTH_STEP;
+    }
+    if controls.just_pressed(Action::WindWeaker) {
+        strength -= STRENGTH_STEP;
+    }
+    if controls.just_pressed(Action::WindGustier) {
+        gustiness += GUSTINESS_STEP;
+    }
+    if controls.just_pressed(Action::WindSteadier) {
+        gustiness -= GUSTINESS_STEP;
+    }
+
+    strength = strength.max(0.0);
+    gustiness = gustiness.clamp(0.0, 1.0);
+
+    if strength == wind.strength && gustiness == wind.gustiness {
+        return;
+    }
+
+    wind.strength = strength;
+    wind.gustiness = gustiness;
+    info!("wind strength {strength:.1} gustiness {gustiness:.1}");
+}